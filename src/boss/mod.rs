@@ -0,0 +1,439 @@
+use crate::enemy::Enemy;
+use crate::health::{DamageCause, DamageEvent, DamageType, Health2d};
+use crate::particle::ParticleEmitter;
+use crate::phase::SpawnEnemyEvent;
+use crate::player::player::Player;
+use crate::shot::ShootEvent;
+use crate::stats::{Stat, Stats};
+use crate::tween::{Ease, Lens, Repeat, RepeatStyle, Tween, TweenStep};
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::Assets;
+use bevy::color::Color;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::f32::consts::TAU;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BossAttackEvent>()
+            .add_systems(Update, update_boss_phase)
+            .add_systems(Update, telegraph_boss_attacks.after(update_boss_phase))
+            .add_systems(Update, boss_attack_listener.after(telegraph_boss_attacks))
+            .add_systems(Update, clear_orphan_telegraphs)
+            .add_systems(Startup, spawn_boss_bars_root)
+            .add_systems(Update, spawn_boss_bar)
+            .add_systems(Update, update_boss_bar);
+    }
+}
+
+const BOSS_BULLET_DAMAGE: f32 = 8.;
+const BOSS_BAR_WIDTH: f32 = 600.;
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum BossAttack {
+    /// Ring of bullets shot from the boss.
+    Radial { bullets: u32 },
+    /// Hits everything within `radius` of where the player stood when the attack was telegraphed.
+    Slam { radius: f32, damage: f32 },
+    /// Spawns `count` enemies of `archetype` around the boss.
+    Summon { archetype: String, count: u32 },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossPhase {
+    /// The phase starts once the boss health fraction drops to this value.
+    pub below: f32,
+    /// Seconds between two attacks.
+    pub interval: f32,
+    /// Seconds the indicator is shown before the attack lands.
+    pub telegraph: f32,
+    /// Used in order, looping.
+    pub attacks: Vec<BossAttack>,
+}
+
+/// Part of an enemy archetype, turns the enemy into a boss.
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossDefinition {
+    pub name: String,
+    /// Sorted from the highest `below` threshold to the lowest.
+    pub phases: Vec<BossPhase>,
+}
+
+impl BossDefinition {
+    pub fn phase_for(&self, health_fraction: f32) -> usize {
+        self.phases
+            .iter()
+            .rposition(|phase| health_fraction <= phase.below)
+            .unwrap_or(0)
+    }
+}
+
+struct PendingAttack {
+    attack: BossAttack,
+    target: Vec3,
+    telegraph: Entity,
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct Boss {
+    pub definition: BossDefinition,
+    pub phase: usize,
+    next_attack: usize,
+    timer: Timer,
+    pending: Option<PendingAttack>,
+}
+
+impl Boss {
+    pub fn new(definition: BossDefinition) -> Self {
+        let interval = definition.phases.first().map_or(1., |phase| phase.interval);
+
+        Self {
+            definition,
+            phase: 0,
+            next_attack: 0,
+            timer: Timer::from_seconds(interval, TimerMode::Repeating),
+            pending: None,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct BossAttackEvent {
+    pub boss: Entity,
+    pub attack: BossAttack,
+    pub target: Vec3,
+}
+
+/// Indicator shown where an attack of `boss` is about to land.
+#[derive(Component)]
+pub struct Telegraph {
+    boss: Entity,
+}
+
+/// Column at the top of the screen holding one [`BossBar`] per living boss.
+#[derive(Component)]
+pub struct BossBars;
+
+#[derive(Component)]
+pub struct BossBar {
+    boss: Entity,
+    fill: Entity,
+}
+
+#[derive(Component)]
+pub struct BossBarFill;
+
+pub fn update_boss_phase(
+    mut commands: Commands,
+    mut boss_query: Query<(&mut Boss, &Health2d, &Transform)>,
+) {
+    for (mut boss, health, transform) in boss_query.iter_mut() {
+        let phase = boss.definition.phase_for(health.health / health.max_health);
+
+        if phase == boss.phase {
+            continue;
+        }
+
+        let Some(interval) = boss
+            .definition
+            .phases
+            .get(phase)
+            .map(|phase| phase.interval)
+        else {
+            continue;
+        };
+
+        boss.phase = phase;
+        boss.next_attack = 0;
+        boss.timer = Timer::from_seconds(interval, TimerMode::Repeating);
+
+        commands.spawn((ParticleEmitter::level_up(), *transform));
+    }
+}
+
+pub fn telegraph_boss_attacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut boss_query: Query<(Entity, &mut Boss, &Enemy, &Transform)>,
+    player_query: Query<&Transform, With<Player>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut event_writer: EventWriter<BossAttackEvent>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    for (entity, mut boss, enemy, transform) in boss_query.iter_mut() {
+        if let Some(pending) = boss.pending.as_mut() {
+            if pending.timer.tick(time.delta()).finished() {
+                let pending = boss.pending.take().unwrap();
+
+                commands.entity(pending.telegraph).despawn();
+
+                event_writer.send(BossAttackEvent {
+                    boss: entity,
+                    attack: pending.attack,
+                    target: pending.target,
+                });
+            }
+
+            continue;
+        }
+
+        if !boss.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        // A boss without phases never attacks
+        let Some(phase) = boss.definition.phases.get(boss.phase) else {
+            continue;
+        };
+
+        let Some(attack) = phase
+            .attacks
+            .get(boss.next_attack % phase.attacks.len().max(1))
+        else {
+            continue;
+        };
+
+        let attack = attack.clone();
+        let telegraph_seconds = phase.telegraph;
+
+        let (target, radius) = match &attack {
+            BossAttack::Slam { radius, .. } => (player_transform.translation, *radius),
+            _ => (transform.translation, enemy.size * 2.5),
+        };
+
+        let telegraph = commands
+            .spawn((
+                Telegraph { boss: entity },
+                Transform::from_translation(target.truncate().extend(0.4))
+                    .with_scale(Vec3::splat(radius)),
+                Mesh2d(meshes.add(Circle::new(1.))),
+                MeshMaterial2d(materials.add(Color::srgba(2., 0.1, 0.1, 0.15))),
+                Tween::new(TweenStep {
+                    lenses: vec![Lens::Alpha {
+                        from: 0.15,
+                        to: 0.5,
+                    }],
+                    duration: 0.2,
+                    ease: Ease::SineInOut,
+                    ..default()
+                })
+                .with_repeat(Repeat::Forever, RepeatStyle::PingPong),
+            ))
+            .id();
+
+        boss.next_attack += 1;
+        boss.pending = Some(PendingAttack {
+            attack,
+            target,
+            telegraph,
+            timer: Timer::from_seconds(telegraph_seconds, TimerMode::Once),
+        });
+    }
+}
+
+pub fn boss_attack_listener(
+    mut commands: Commands,
+    mut event_reader: EventReader<BossAttackEvent>,
+    boss_query: Query<&Transform, With<Boss>>,
+    player_query: Query<(Entity, &Stats, &Transform), With<Player>>,
+    mut shoot_writer: EventWriter<ShootEvent>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut spawn_enemy_event_writter: EventWriter<SpawnEnemyEvent>,
+) {
+    for event in event_reader.read() {
+        // The boss died while the attack was telegraphed
+        let Ok(boss_transform) = boss_query.get(event.boss) else {
+            continue;
+        };
+
+        match &event.attack {
+            BossAttack::Radial { bullets } => {
+                for index in 0..*bullets {
+                    shoot_writer.send(ShootEvent {
+                        damage: BOSS_BULLET_DAMAGE,
+                        damage_type: DamageType::Shock,
+                        source: Some(event.boss),
+                        on_hit: Vec::new(),
+                        direction: Vec2::from_angle(TAU * index as f32 / *bullets as f32),
+                        position: *boss_transform,
+                        hostile: true,
+                    });
+                }
+            }
+            BossAttack::Slam { radius, damage } => {
+                commands.spawn((
+                    ParticleEmitter::death_burst(),
+                    Transform::from_translation(event.target),
+                ));
+
+                if let Ok((player, stats, player_transform)) = player_query.get_single() {
+                    let distance = player_transform.translation.distance(event.target);
+
+                    if distance < radius + stats.get(Stat::Size) {
+                        damage_writer.send(DamageEvent {
+                            entity: player,
+                            damage: *damage,
+                            damage_type: DamageType::Kinetic,
+                            source: Some(event.boss),
+                            cause: DamageCause::Attack,
+                            unmitigated: false,
+                            knockback: None,
+                        });
+                    }
+                }
+            }
+            BossAttack::Summon { archetype, count } => {
+                for index in 0..*count {
+                    let direction = Vec2::from_angle(TAU * index as f32 / *count as f32);
+
+                    spawn_enemy_event_writter.send(SpawnEnemyEvent {
+                        position: Some(boss_transform.translation),
+                        impulse: direction * 400.,
+                        ..SpawnEnemyEvent::new(archetype)
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Removes the indicators of bosses that died before their attack landed.
+pub fn clear_orphan_telegraphs(
+    mut commands: Commands,
+    telegraph_query: Query<(Entity, &Telegraph)>,
+    boss_query: Query<(), With<Boss>>,
+) {
+    for (entity, telegraph) in telegraph_query.iter() {
+        if !boss_query.contains(telegraph.boss) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn spawn_boss_bars_root(mut commands: Commands) {
+    commands.spawn((
+        BossBars,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(16.),
+            width: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+    ));
+}
+
+pub fn spawn_boss_bar(
+    mut commands: Commands,
+    boss_query: Query<(Entity, &Boss), Added<Boss>>,
+    root_query: Query<Entity, With<BossBars>>,
+) {
+    let Ok(root) = root_query.get_single() else {
+        return;
+    };
+
+    for (entity, boss) in boss_query.iter() {
+        let fill = commands
+            .spawn((
+                BossBarFill,
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.9, 0.1, 0.3)),
+            ))
+            .id();
+
+        let background = commands
+            .spawn((
+                Node {
+                    width: Val::Px(BOSS_BAR_WIDTH),
+                    height: Val::Px(16.),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+            ))
+            .add_child(fill)
+            .id();
+
+        let bar = commands
+            .spawn((
+                BossBar { boss: entity, fill },
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+            ))
+            .with_children(|bar| {
+                bar.spawn((
+                    Text::new(boss.definition.name.clone()),
+                    TextFont {
+                        font_size: 20.,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+            })
+            .add_child(background)
+            .id();
+
+        commands.entity(root).add_child(bar);
+    }
+}
+
+pub fn update_boss_bar(
+    mut commands: Commands,
+    boss_query: Query<&Health2d, With<Boss>>,
+    bar_query: Query<(Entity, &BossBar)>,
+    mut fill_query: Query<&mut Node, With<BossBarFill>>,
+) {
+    for (entity, bar) in bar_query.iter() {
+        let Ok(health) = boss_query.get(bar.boss) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        if let Ok(mut node) = fill_query.get_mut(bar.fill) {
+            node.width = Val::Percent((health.health / health.max_health).clamp(0., 1.) * 100.);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phase(below: f32) -> BossPhase {
+        BossPhase {
+            below,
+            interval: 1.,
+            telegraph: 1.,
+            attacks: vec![BossAttack::Radial { bullets: 8 }],
+        }
+    }
+
+    #[test]
+    fn phase_follows_health_thresholds() {
+        let definition = BossDefinition {
+            name: String::new(),
+            phases: vec![phase(1.), phase(0.6), phase(0.3)],
+        };
+
+        assert_eq!(definition.phase_for(1.), 0);
+        assert_eq!(definition.phase_for(0.61), 0);
+        assert_eq!(definition.phase_for(0.6), 1);
+        assert_eq!(definition.phase_for(0.1), 2);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::health::{DamageDealtEvent, DamageType};
+use crate::tween::{Ease, Lens, Tween, TweenCompleted, TweenStep};
+use bevy::app::{App, Plugin, Update};
+use bevy::color::Color;
+use bevy::math::Vec3;
+use bevy::prelude::*;
+use rand::random;
+use std::collections::HashMap;
+
+/// Tags the damage number tweens in [`TweenCompleted`].
+const DAMAGE_NUMBER_TWEEN: u32 = 1;
+/// Largest tilt, in radians, a critical number starts with before straightening up.
+const CRITICAL_TILT: f32 = 0.4;
+
+pub struct DamageNumberPlugin;
+
+impl Plugin for DamageNumberPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageNumberSettings>()
+            .init_resource::<DamageNumberPool>()
+            .add_systems(Update, release_damage_numbers)
+            .add_systems(Update, spawn_damage_numbers.after(release_damage_numbers));
+    }
+}
+
+#[derive(Resource)]
+pub struct DamageNumberSettings {
+    /// Sums hits on the same target into one number while they keep coming.
+    pub aggregate: bool,
+    /// Seconds after the last hit during which a new hit is added to the same number.
+    pub aggregate_window: f32,
+
+    /// Colour of kinetic damage.
+    pub color: Color,
+    pub fire_color: Color,
+    pub shock_color: Color,
+    pub poison_color: Color,
+    /// Colour of hits fully taken by a shield.
+    pub shield_color: Color,
+    pub critical_color: Color,
+    pub font_size: f32,
+    pub critical_scale: f32,
+
+    pub rise: f32,
+    pub lifetime: f32,
+}
+
+impl Default for DamageNumberSettings {
+    fn default() -> Self {
+        Self {
+            aggregate: true,
+            aggregate_window: 0.25,
+            color: Color::srgb(5., 5., 5.),
+            fire_color: Color::srgb(6., 2.5, 0.3),
+            shock_color: Color::srgb(1.5, 3., 7.),
+            poison_color: Color::srgb(1., 5., 0.8),
+            shield_color: Color::srgb(1., 4., 5.),
+            critical_color: Color::srgb(7.5, 2., 0.),
+            font_size: 12.,
+            critical_scale: 1.6,
+            rise: 40.,
+            lifetime: 0.8,
+        }
+    }
+}
+
+impl DamageNumberSettings {
+    /// `None` for hits fully taken by a shield.
+    fn type_color(&self, damage_type: Option<DamageType>) -> Color {
+        match damage_type {
+            None => self.shield_color,
+            Some(DamageType::Kinetic) => self.color,
+            Some(DamageType::Fire) => self.fire_color,
+            Some(DamageType::Shock) => self.shock_color,
+            Some(DamageType::Poison) => self.poison_color,
+        }
+    }
+
+    /// Colour and font scale of a number.
+    fn style(&self, number: &ActiveNumber) -> (Color, f32) {
+        if number.critical {
+            (self.critical_color, self.critical_scale)
+        } else {
+            (self.type_color(number.damage_type), 1.)
+        }
+    }
+}
+
+struct ActiveNumber {
+    entity: Entity,
+    total: f32,
+    critical: bool,
+    /// Type of the latest hit, `None` when a shield took all of it.
+    damage_type: Option<DamageType>,
+    last_hit: f32,
+    /// Where the number started rising, kept so aggregated hits do not move it sideways.
+    start: Vec3,
+    /// Tilt the number straightens up from once it is critical.
+    tilt: f32,
+}
+
+/// Hidden number entities ready to be reused and the numbers currently shown per target.
+#[derive(Resource, Default)]
+pub struct DamageNumberPool {
+    free: Vec<Entity>,
+    active: HashMap<Entity, ActiveNumber>,
+}
+
+#[derive(Component)]
+pub struct DamageNumber;
+
+fn damage_number_text(settings: &DamageNumberSettings, number: &ActiveNumber) -> impl Bundle {
+    let (color, scale) = settings.style(number);
+
+    (
+        Text2d::new(format!("{:.0}", number.total.ceil())),
+        TextFont {
+            font_size: settings.font_size * scale,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+/// Rises from `from` to the top of the number path while fading out.
+fn damage_number_tween(
+    settings: &DamageNumberSettings,
+    number: &ActiveNumber,
+    from: Vec3,
+) -> Tween {
+    let (color, _) = settings.style(number);
+
+    let mut lenses = vec![Lens::Translation {
+        from,
+        to: number.start + Vec3::Y * settings.rise,
+    }];
+
+    // Critical hits flash white into their colour and straighten up from a tilt
+    if number.critical {
+        lenses.push(Lens::Color {
+            from: Color::WHITE,
+            to: color,
+        });
+        lenses.push(Lens::Rotation {
+            from: number.tilt,
+            to: 0.,
+        });
+    }
+
+    // Applied after the colour, which would otherwise reset the alpha
+    lenses.push(Lens::Alpha { from: 1., to: 0. });
+
+    Tween::new(TweenStep {
+        lenses,
+        duration: settings.lifetime,
+        ease: Ease::CubicOut,
+        ..default()
+    })
+    .with_tag(DAMAGE_NUMBER_TWEEN)
+}
+
+pub fn spawn_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<DamageNumberSettings>,
+    mut pool: ResMut<DamageNumberPool>,
+    mut event_reader: EventReader<DamageDealtEvent>,
+    victim_query: Query<&Transform, Without<DamageNumber>>,
+    number_query: Query<&Transform, With<DamageNumber>>,
+) {
+    let now = time.elapsed_secs();
+
+    for event in event_reader.read() {
+        let Ok(victim_transform) = victim_query.get(event.entity) else {
+            continue;
+        };
+
+        let damage_type = (event.absorbed < event.damage).then_some(event.damage_type);
+
+        if settings.aggregate {
+            if let Some(active) = pool.active.get_mut(&event.entity) {
+                if now - active.last_hit < settings.aggregate_window {
+                    active.total += event.damage;
+                    active.critical |= event.critical;
+                    active.damage_type = damage_type;
+                    active.last_hit = now;
+
+                    // Keeps rising from where it is instead of jumping back to the victim
+                    let from = number_query
+                        .get(active.entity)
+                        .map_or(active.start, |transform| transform.translation);
+
+                    commands.entity(active.entity).insert((
+                        damage_number_text(&settings, active),
+                        damage_number_tween(&settings, active, from),
+                    ));
+                    continue;
+                }
+            }
+        }
+
+        let entity = pool
+            .free
+            .pop()
+            .unwrap_or_else(|| commands.spawn_empty().id());
+
+        let number = ActiveNumber {
+            entity,
+            total: event.damage,
+            critical: event.critical,
+            damage_type,
+            last_hit: now,
+            start: victim_transform.translation + Vec3::new((random::<f32>() - 0.5) * 10., 0., 2.),
+            tilt: (random::<f32>() - 0.5) * 2. * CRITICAL_TILT,
+        };
+
+        commands.entity(entity).insert((
+            DamageNumber,
+            damage_number_text(&settings, &number),
+            Transform::from_translation(number.start),
+            Visibility::Visible,
+            damage_number_tween(&settings, &number, number.start),
+        ));
+
+        pool.active.insert(event.entity, number);
+    }
+}
+
+pub fn release_damage_numbers(
+    mut commands: Commands,
+    mut pool: ResMut<DamageNumberPool>,
+    mut event_reader: EventReader<TweenCompleted>,
+    number_query: Query<&Tween, With<DamageNumber>>,
+) {
+    for event in event_reader.read() {
+        if event.tag != DAMAGE_NUMBER_TWEEN {
+            continue;
+        }
+
+        // A number restarted by an aggregated hit is still in use
+        let Ok(tween) = number_query.get(event.entity) else {
+            continue;
+        };
+        if !tween.finished() {
+            continue;
+        }
+
+        commands.entity(event.entity).insert(Visibility::Hidden);
+
+        pool.active
+            .retain(|_, active| active.entity != event.entity);
+        pool.free.push(event.entity);
+    }
+}
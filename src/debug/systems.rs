@@ -1,6 +1,8 @@
+use crate::health::Health2d;
 use crate::phase::PhaseState;
 use crate::player::player::Player;
 use crate::shared::InfoText;
+use crate::wave::WaveDirector;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::{Query, Res, Text, With};
 
@@ -8,7 +10,8 @@ pub fn show_info(
     state: Res<PhaseState>,
     mut text_query: Query<&mut Text, With<InfoText>>,
     diagnostics: Res<DiagnosticsStore>,
-    player_query: Query<&Player>,
+    player_query: Query<(&Player, &Health2d)>,
+    director: Res<WaveDirector>,
 ) {
     let mut text_info = format!("Score: {:.0}\n", state.score());
 
@@ -20,12 +23,38 @@ pub fn show_info(
         }
     }
 
-    if let Ok(player) = player_query.get_single() {
-        text_info.push_str(format!("Player Health: {:.2}\n", player.max_health).as_str());
+    if let Ok((player, health)) = player_query.get_single() {
+        text_info.push_str(
+            format!(
+                "Player Health: {:.2}/{}\n",
+                health.health, health.max_health
+            )
+            .as_str(),
+        );
         text_info.push_str(format!("Player XP: {:.1}\n", player.xp).as_str());
     }
 
-    text_info.push_str(format!("Spawn Time: {:.1}\n", state.enemy_spawn_time).as_str());
+    text_info.push_str(format!("Damage Dealt: {:.0}\n", state.damage_dealt).as_str());
+
+    let mut kills_by_type: Vec<String> = state
+        .kills_by_type
+        .iter()
+        .map(|(damage_type, kills)| format!("{damage_type:?} {kills}"))
+        .collect();
+    kills_by_type.sort();
+
+    text_info.push_str(
+        format!(
+            "Kills: {} (pickups {})\n",
+            kills_by_type.join(", "),
+            state.pickup_kills
+        )
+        .as_str(),
+    );
+
+    if let Some(wave) = director.wave {
+        text_info.push_str(format!("Wave: {}\n", wave + 1).as_str());
+    }
 
     text_query.get_single_mut().unwrap().0 = text_info;
 }
@@ -0,0 +1,183 @@
+use crate::boss::BossDefinition;
+use crate::enemy::behaviour::Behaviour;
+use crate::enemy::steering::Steering;
+use crate::enemy::SplitOnDeath;
+use crate::health::Resistances;
+use bevy::asset::LoadState;
+use bevy::color::Color;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Every enemy type that can be spawned, loaded from a `.archetypes.ron` file.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct EnemyArchetypes {
+    pub archetypes: Vec<EnemyArchetype>,
+}
+
+#[derive(Resource)]
+pub struct EnemyArchetypesHandle(pub Handle<EnemyArchetypes>);
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnemyArchetype {
+    pub id: String,
+    pub health: f32,
+    pub size: f32,
+    /// Random speed between both values, in either order.
+    pub speed: (f32, f32),
+    /// sRGB colour, values above 1 glow with bloom.
+    pub color: (f32, f32, f32),
+    pub xp: u32,
+    /// Threat budget the spawn director spends to spawn one.
+    pub cost: f32,
+    /// Damage dealt to the player on touch.
+    pub contact_damage: f32,
+    #[serde(default)]
+    pub behaviour: Behaviour,
+    /// Name of the drop table rolled on death.
+    #[serde(default)]
+    pub drops: Option<String>,
+    /// Leaves a particle trail behind.
+    #[serde(default)]
+    pub trail: bool,
+    #[serde(default)]
+    pub split: Option<SplitOnDeath>,
+    #[serde(default)]
+    pub steering: Steering,
+    #[serde(default)]
+    pub boss: Option<BossDefinition>,
+    #[serde(default)]
+    pub resistances: Resistances,
+}
+
+impl EnemyArchetype {
+    pub fn color(&self) -> Color {
+        Color::srgb(self.color.0, self.color.1, self.color.2)
+    }
+
+    pub fn roll_speed(&self, rng: &mut impl Rng) -> f32 {
+        let (a, b) = self.speed;
+
+        rng.gen_range(a.min(b)..=a.max(b))
+    }
+}
+
+pub struct ArchetypeMesh {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<ColorMaterial>,
+}
+
+/// Mesh and material of each archetype, rebuilt whenever the archetypes file is (re)loaded.
+#[derive(Resource, Default)]
+pub struct ArchetypeMeshes(HashMap<String, ArchetypeMesh>);
+
+/// The loaded archetypes, nothing is available until the asset server finished loading them.
+#[derive(SystemParam)]
+pub struct CurrentEnemyArchetypes<'w> {
+    handle: Res<'w, EnemyArchetypesHandle>,
+    archetypes: Res<'w, Assets<EnemyArchetypes>>,
+    meshes: Res<'w, ArchetypeMeshes>,
+    asset_server: Res<'w, AssetServer>,
+}
+
+impl CurrentEnemyArchetypes<'_> {
+    pub fn loaded(&self) -> bool {
+        !self.meshes.0.is_empty()
+    }
+
+    /// The file could not be loaded, e.g. it does not parse, nothing will be available until
+    /// it is fixed.
+    pub fn failed(&self) -> bool {
+        matches!(
+            self.asset_server.load_state(&self.handle.0),
+            LoadState::Failed(_)
+        )
+    }
+
+    pub fn get(&self, id: &str) -> Option<(&EnemyArchetype, &ArchetypeMesh)> {
+        let archetype = self
+            .archetypes
+            .get(&self.handle.0)?
+            .archetypes
+            .iter()
+            .find(|archetype| archetype.id == id)?;
+
+        Some((archetype, self.meshes.0.get(id)?))
+    }
+}
+
+pub fn load_enemy_archetypes(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(EnemyArchetypesHandle(
+        asset_server.load("enemies.archetypes.ron"),
+    ));
+}
+
+pub fn prepare_archetype_meshes(
+    mut event_reader: EventReader<AssetEvent<EnemyArchetypes>>,
+    archetypes: Res<Assets<EnemyArchetypes>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut archetype_meshes: ResMut<ArchetypeMeshes>,
+) {
+    for event in event_reader.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+
+        let Some(archetypes) = archetypes.get(*id) else {
+            continue;
+        };
+
+        archetype_meshes.0 = archetypes
+            .archetypes
+            .iter()
+            .map(|archetype| {
+                let mesh = meshes.add(Circle::new(archetype.size));
+                let material = materials.add(ColorMaterial::from(archetype.color()));
+
+                (archetype.id.clone(), ArchetypeMesh { mesh, material })
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_archetypes_parse() {
+        let archetypes: EnemyArchetypes =
+            bevy::asset::ron::de::from_str(include_str!("../../assets/enemies.archetypes.ron"))
+                .unwrap();
+
+        for archetype in archetypes.archetypes.iter() {
+            assert!(archetype.health > 0.);
+            assert!(archetype.speed.0 <= archetype.speed.1);
+
+            if let Some(split) = &archetype.split {
+                assert!(archetypes
+                    .archetypes
+                    .iter()
+                    .any(|other| other.id == split.child_archetype));
+            }
+        }
+    }
+
+    #[test]
+    fn speed_is_rolled_between_either_order() {
+        let archetype: EnemyArchetype = bevy::asset::ron::de::from_str(
+            "(id: \"slow\", health: 1., size: 1., speed: (80., 40.), color: (1., 1., 1.), \
+             xp: 1, cost: 1., contact_damage: 1.)",
+        )
+        .unwrap();
+
+        for _ in 0..100 {
+            let speed = archetype.roll_speed(&mut rand::thread_rng());
+            assert!((40. ..=80.).contains(&speed));
+        }
+    }
+}
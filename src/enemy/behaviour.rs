@@ -0,0 +1,344 @@
+use crate::health::status::StatusEffects;
+use crate::health::{DamageType, Health2d};
+use crate::player::player::Player;
+use crate::shot::ShootEvent;
+use crate::utils::random_direction;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::time::Duration;
+
+const DASH_INTERVAL: f32 = 2.;
+const DASH_DURATION: f32 = 0.4;
+const DASH_SPEED_MULTIPLIER: f32 = 3.;
+const REST_SPEED_MULTIPLIER: f32 = 0.3;
+
+/// Seconds a charger waits after a charge before it can wind up again.
+const CHARGE_COOLDOWN: f32 = 1.5;
+const FLEE_SPEED_MULTIPLIER: f32 = 1.3;
+const WANDER_SPEED_MULTIPLIER: f32 = 0.6;
+/// How much wandering enemies still drift toward the player.
+const WANDER_PLAYER_BIAS: f32 = 0.3;
+const ENEMY_BULLET_DAMAGE: f32 = 5.;
+
+/// How the enemy decides where to go, set per archetype.
+#[derive(Component, Deserialize, Clone, PartialEq, Default, Debug)]
+#[require(Intent, BehaviourState)]
+pub enum Behaviour {
+    /// Walks straight at the player.
+    #[default]
+    Chase,
+    /// Rests, then bursts toward the player.
+    Dash,
+    /// Circles the player at `distance`.
+    Orbit { distance: f32 },
+    /// Approaches to `range`, stops for `wind_up` seconds, then charges in a straight line.
+    Charge {
+        range: f32,
+        wind_up: f32,
+        duration: f32,
+        speed_multiplier: f32,
+    },
+    /// Keeps `distance` from the player and shoots `fire_rate` bullets per second at it.
+    Kite { distance: f32, fire_rate: f32 },
+    /// Chases until its health drops below the `below` fraction, then runs away.
+    Flee { below: f32 },
+    /// Walks in a random heading that changes every `change_every` seconds.
+    Wander { change_every: f32 },
+}
+
+/// Where the behaviour wants to go this frame, steering and movement follow it.
+#[derive(Component)]
+pub struct Intent {
+    /// Not normalized, zero to stand still.
+    pub heading: Vec2,
+    pub speed_multiplier: f32,
+}
+
+impl Default for Intent {
+    fn default() -> Self {
+        Self {
+            heading: Vec2::ZERO,
+            speed_multiplier: 1.,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum ChargePhase {
+    #[default]
+    Approach,
+    WindUp,
+    Charge,
+    Cooldown,
+}
+
+/// Runtime state shared by the behaviours that need one.
+#[derive(Component, Default)]
+pub struct BehaviourState {
+    timer: Timer,
+    charge: ChargePhase,
+    /// Locked charge heading or current wander heading.
+    heading: Vec2,
+}
+
+impl BehaviourState {
+    fn start(&mut self, seconds: f32) {
+        self.timer = Timer::from_seconds(seconds, TimerMode::Once);
+    }
+}
+
+impl Behaviour {
+    /// Updates the state and returns the intent, plus whether the enemy shoots this frame.
+    pub fn update(
+        &self,
+        state: &mut BehaviourState,
+        to_player: Vec2,
+        health_fraction: f32,
+        delta: Duration,
+    ) -> (Intent, bool) {
+        let chase = Intent {
+            heading: to_player,
+            speed_multiplier: 1.,
+        };
+
+        let finished = state.timer.tick(delta).finished();
+
+        match self {
+            Behaviour::Chase => (chase, false),
+            Behaviour::Dash => {
+                if finished {
+                    state.start(DASH_INTERVAL);
+                }
+
+                let dashing = state.timer.elapsed_secs() > DASH_INTERVAL - DASH_DURATION;
+
+                let speed_multiplier = if dashing {
+                    DASH_SPEED_MULTIPLIER
+                } else {
+                    REST_SPEED_MULTIPLIER
+                };
+
+                (
+                    Intent {
+                        speed_multiplier,
+                        ..chase
+                    },
+                    false,
+                )
+            }
+            Behaviour::Orbit { distance } => (
+                Intent {
+                    heading: orbit_heading(to_player, *distance),
+                    speed_multiplier: 1.,
+                },
+                false,
+            ),
+            Behaviour::Charge {
+                range,
+                wind_up,
+                duration,
+                speed_multiplier,
+            } => {
+                let charge = |heading: Vec2| Intent {
+                    heading,
+                    speed_multiplier: *speed_multiplier,
+                };
+                // Stands still facing the player
+                let wind_up_intent = Intent {
+                    heading: to_player,
+                    speed_multiplier: 0.,
+                };
+
+                let (phase, intent) = match state.charge {
+                    ChargePhase::Approach if to_player.length() < *range => {
+                        state.start(*wind_up);
+                        (ChargePhase::WindUp, wind_up_intent)
+                    }
+                    ChargePhase::WindUp if finished => {
+                        // The heading is locked when the wind up ends
+                        state.start(*duration);
+                        state.heading = to_player;
+                        (ChargePhase::Charge, charge(to_player))
+                    }
+                    ChargePhase::WindUp => (ChargePhase::WindUp, wind_up_intent),
+                    ChargePhase::Charge if finished => {
+                        state.start(CHARGE_COOLDOWN);
+                        (ChargePhase::Cooldown, chase)
+                    }
+                    ChargePhase::Charge => (ChargePhase::Charge, charge(state.heading)),
+                    ChargePhase::Cooldown if finished => (ChargePhase::Approach, chase),
+                    phase => (phase, chase),
+                };
+
+                state.charge = phase;
+
+                (intent, false)
+            }
+            Behaviour::Kite {
+                distance,
+                fire_rate,
+            } => {
+                // The default timer is already finished, so the first bullet leaves right away
+                let shoot = finished;
+
+                if shoot {
+                    state.start(1. / fire_rate);
+                }
+
+                (
+                    Intent {
+                        heading: orbit_heading(to_player, *distance),
+                        speed_multiplier: 1.,
+                    },
+                    shoot,
+                )
+            }
+            Behaviour::Flee { below } if health_fraction < *below => (
+                Intent {
+                    heading: -to_player,
+                    speed_multiplier: FLEE_SPEED_MULTIPLIER,
+                },
+                false,
+            ),
+            Behaviour::Flee { .. } => (chase, false),
+            Behaviour::Wander { change_every } => {
+                if finished || state.heading == Vec2::ZERO {
+                    state.start(*change_every);
+                    state.heading = random_direction();
+                }
+
+                (
+                    Intent {
+                        heading: state.heading + to_player.normalize_or_zero() * WANDER_PLAYER_BIAS,
+                        speed_multiplier: WANDER_SPEED_MULTIPLIER,
+                    },
+                    false,
+                )
+            }
+        }
+    }
+}
+
+/// Circles around the player, moving in or out until at `distance`.
+fn orbit_heading(to_player: Vec2, distance: f32) -> Vec2 {
+    let radial = to_player.normalize_or_zero();
+    let error = ((to_player.length() - distance) / distance).clamp(-1., 1.);
+
+    radial.perp() + radial * error * 2.
+}
+
+type BehavingEnemy<'a> = (
+    Entity,
+    &'a Behaviour,
+    &'a mut BehaviourState,
+    &'a mut Intent,
+    &'a Transform,
+    &'a Health2d,
+    Option<&'a StatusEffects>,
+);
+
+pub fn behave_enemies(
+    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<BehavingEnemy>,
+    mut shoot_writer: EventWriter<ShootEvent>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    for (entity, behaviour, mut state, mut intent, transform, health, effects) in
+        enemy_query.iter_mut()
+    {
+        let to_player = (player_transform.translation - transform.translation).truncate();
+
+        let (new_intent, shoot) = behaviour.update(
+            &mut state,
+            to_player,
+            health.health / health.max_health,
+            time.delta(),
+        );
+
+        *intent = new_intent;
+
+        // Stunned enemies keep their behaviour running but hold fire
+        if shoot && effects.is_none_or(StatusEffects::can_act) {
+            shoot_writer.send(ShootEvent {
+                damage: ENEMY_BULLET_DAMAGE,
+                damage_type: DamageType::Poison,
+                source: Some(entity),
+                on_hit: Vec::new(),
+                direction: to_player.normalize_or_zero(),
+                position: *transform,
+                hostile: true,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn orbit_moves_sideways_at_the_distance() {
+        let heading = orbit_heading(Vec2::new(100., 0.), 100.);
+
+        assert_eq!(heading, Vec2::Y);
+        assert!(orbit_heading(Vec2::new(300., 0.), 100.).x > 0.);
+        assert!(orbit_heading(Vec2::new(50., 0.), 100.).x < 0.);
+    }
+
+    #[test]
+    fn flee_only_below_threshold() {
+        let behaviour = Behaviour::Flee { below: 0.5 };
+        let mut state = BehaviourState::default();
+
+        let (intent, _) = behaviour.update(&mut state, Vec2::X, 1., FRAME);
+        assert_eq!(intent.heading, Vec2::X);
+
+        let (intent, _) = behaviour.update(&mut state, Vec2::X, 0.2, FRAME);
+        assert_eq!(intent.heading, -Vec2::X);
+    }
+
+    #[test]
+    fn charge_winds_up_then_charges_in_a_locked_direction() {
+        let behaviour = Behaviour::Charge {
+            range: 100.,
+            wind_up: 0.15,
+            duration: 1.,
+            speed_multiplier: 4.,
+        };
+        let mut state = BehaviourState::default();
+
+        let (intent, _) = behaviour.update(&mut state, Vec2::new(50., 0.), 1., FRAME);
+        assert_eq!(state.charge, ChargePhase::WindUp);
+        assert_eq!(intent.speed_multiplier, 0.);
+
+        behaviour.update(&mut state, Vec2::new(50., 0.), 1., FRAME);
+        behaviour.update(&mut state, Vec2::new(50., 0.), 1., FRAME);
+        assert_eq!(state.charge, ChargePhase::Charge);
+
+        // The player moved but the charge keeps going the same way
+        let (intent, _) = behaviour.update(&mut state, Vec2::new(0., 50.), 1., FRAME);
+        assert_eq!(intent.heading, Vec2::new(50., 0.));
+        assert_eq!(intent.speed_multiplier, 4.);
+    }
+
+    #[test]
+    fn kite_shoots_at_fire_rate() {
+        let behaviour = Behaviour::Kite {
+            distance: 200.,
+            fire_rate: 2.,
+        };
+        let mut state = BehaviourState::default();
+
+        let shots = (0..10)
+            .filter(|_| behaviour.update(&mut state, Vec2::X, 1., FRAME).1)
+            .count();
+
+        assert_eq!(shots, 2);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::enemy::Enemy;
+use crate::health::{DamageCause, DamageEvent, DamageType, DeathEvent, Health2d, Shield};
+use crate::particle::ParticleEmitter;
+use crate::phase::SpawnEnemyEvent;
+use crate::player::player::Player;
+use crate::stats::{Stat, Stats};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+/// Chance for a regular enemy to spawn as an elite.
+pub const ELITE_CHANCE: f32 = 0.05;
+const MAX_AFFIXES: usize = 2;
+
+const ELITE_HEALTH_MULTIPLIER: f32 = 2.5;
+const ELITE_XP_MULTIPLIER: u32 = 3;
+
+/// Shield, as a fraction of the max health.
+const SHIELD_FRACTION: f32 = 0.5;
+const HASTE_MULTIPLIER: f32 = 1.5;
+/// Health regenerated per second, as a fraction of the max health.
+const REGENERATION_FRACTION: f32 = 0.05;
+const EXPLOSION_RADIUS: f32 = 80.;
+const EXPLOSION_DAMAGE: f32 = 15.;
+const SUMMON_INTERVAL: f32 = 4.;
+const SUMMON_COUNT: u32 = 2;
+const SUMMON_ARCHETYPE: &str = "swarmer";
+const SUMMON_SPEED: f32 = 250.;
+
+/// How much bigger than the enemy the outline is drawn.
+const OUTLINE_SCALE: f32 = 1.25;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Affix {
+    /// Spawns with a shield that absorbs damage first.
+    Shielded,
+    /// Moves faster.
+    Hasted,
+    /// Heals over time.
+    Regenerating,
+    /// Blows up on death, hurting the player when close.
+    Explosive,
+    /// Keeps spawning minions around itself.
+    Summoner,
+}
+
+const AFFIXES: [Affix; 5] = [
+    Affix::Shielded,
+    Affix::Hasted,
+    Affix::Regenerating,
+    Affix::Explosive,
+    Affix::Summoner,
+];
+
+impl Affix {
+    fn color(&self) -> Color {
+        match self {
+            Affix::Shielded => Color::srgb(0.3, 1.5, 4.),
+            Affix::Hasted => Color::srgb(4., 4., 0.5),
+            Affix::Regenerating => Color::srgb(0.3, 4., 0.5),
+            Affix::Explosive => Color::srgb(5., 1., 0.2),
+            Affix::Summoner => Color::srgb(3., 0.5, 4.),
+        }
+    }
+}
+
+/// Stronger variant of an enemy, the affixes are applied once when it spawns.
+#[derive(Component)]
+pub struct Elite {
+    pub affixes: Vec<Affix>,
+}
+
+impl Elite {
+    /// Picks between one and [`MAX_AFFIXES`] distinct affixes.
+    pub fn roll(rng: &mut impl Rng) -> Self {
+        let count = rng.gen_range(1..=MAX_AFFIXES);
+
+        Self {
+            affixes: AFFIXES.choose_multiple(rng, count).copied().collect(),
+        }
+    }
+
+    pub fn has(&self, affix: Affix) -> bool {
+        self.affixes.contains(&affix)
+    }
+}
+
+#[derive(Component)]
+pub struct Summoner {
+    timer: Timer,
+}
+
+pub fn apply_elite_affixes(
+    mut commands: Commands,
+    mut elite_query: Query<(Entity, &Elite, &mut Enemy, &mut Health2d), Added<Elite>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, elite, mut enemy, mut health) in elite_query.iter_mut() {
+        health.max_health *= ELITE_HEALTH_MULTIPLIER;
+        health.health = health.max_health;
+        health.xp_on_death *= ELITE_XP_MULTIPLIER;
+
+        let mut entity_commands = commands.entity(entity);
+
+        for affix in elite.affixes.iter() {
+            match affix {
+                Affix::Shielded => {
+                    health.shield = Some(Shield::new(health.max_health * SHIELD_FRACTION));
+                }
+                Affix::Hasted => enemy.speed *= HASTE_MULTIPLIER,
+                Affix::Regenerating => {
+                    health.regeneration = health.max_health * REGENERATION_FRACTION;
+                }
+                Affix::Explosive => {}
+                Affix::Summoner => {
+                    entity_commands.insert(Summoner {
+                        timer: Timer::from_seconds(SUMMON_INTERVAL, TimerMode::Repeating),
+                    });
+                }
+            }
+        }
+
+        // The outline takes the colour of the first affix, drawn behind the enemy
+        entity_commands.with_child((
+            Mesh2d(meshes.add(Circle::new(enemy.size * OUTLINE_SCALE))),
+            MeshMaterial2d(materials.add(elite.affixes[0].color())),
+            Transform::from_xyz(0., 0., -0.1),
+        ));
+    }
+}
+
+pub fn summon_minions(
+    time: Res<Time>,
+    mut summoner_query: Query<(&mut Summoner, &Transform)>,
+    mut spawn_enemy_event_writter: EventWriter<SpawnEnemyEvent>,
+) {
+    for (mut summoner, transform) in summoner_query.iter_mut() {
+        if !summoner.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let offset = rand::random::<f32>() * TAU;
+
+        for index in 0..SUMMON_COUNT {
+            let direction = Vec2::from_angle(offset + TAU * index as f32 / SUMMON_COUNT as f32);
+
+            spawn_enemy_event_writter.send(SpawnEnemyEvent {
+                position: Some(transform.translation),
+                impulse: direction * SUMMON_SPEED,
+                ..SpawnEnemyEvent::new(SUMMON_ARCHETYPE)
+            });
+        }
+    }
+}
+
+pub fn explode_on_death(
+    mut commands: Commands,
+    mut event_reader: EventReader<DeathEvent>,
+    elite_query: Query<&Elite>,
+    player_query: Query<(Entity, &Stats, &Transform), With<Player>>,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    for event in event_reader.read() {
+        let Ok(elite) = elite_query.get(event.entity) else {
+            continue;
+        };
+
+        if !elite.has(Affix::Explosive) {
+            continue;
+        }
+
+        commands.spawn((
+            ParticleEmitter::explosion(),
+            Transform::from_translation(event.position),
+        ));
+
+        if let Ok((player, stats, player_transform)) = player_query.get_single() {
+            let distance = player_transform.translation.distance(event.position);
+
+            if distance < EXPLOSION_RADIUS + stats.get(Stat::Size) {
+                damage_writer.send(DamageEvent {
+                    entity: player,
+                    damage: EXPLOSION_DAMAGE,
+                    damage_type: DamageType::Fire,
+                    source: Some(event.entity),
+                    cause: DamageCause::Attack,
+                    unmitigated: false,
+                    knockback: None,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_distinct_affixes() {
+        for _ in 0..100 {
+            let elite = Elite::roll(&mut rand::thread_rng());
+
+            assert!((1..=MAX_AFFIXES).contains(&elite.affixes.len()));
+            assert!(elite
+                .affixes
+                .iter()
+                .enumerate()
+                .all(|(index, affix)| !elite.affixes[index + 1..].contains(affix)));
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::boss::Boss;
+use crate::enemy::{Enemy, Impulse};
+use crate::health::DamageEvent;
+use bevy::prelude::*;
+
+/// Enemy size the knockback strength is given for, bigger enemies are pushed less.
+const KNOCKBACK_REFERENCE_SIZE: f32 = 10.;
+const HIT_STUN_SECONDS: f32 = 0.15;
+const HIT_FLASH_SECONDS: f32 = 0.08;
+
+/// The enemy stops walking on its own while the knockback pushes it.
+#[derive(Component)]
+pub struct HitStun {
+    timer: Timer,
+}
+
+/// Swaps the material for a white one until the timer finishes.
+#[derive(Component)]
+pub struct HitFlash {
+    timer: Timer,
+    original: Handle<ColorMaterial>,
+}
+
+#[derive(Resource)]
+pub struct HitFlashMaterial(Handle<ColorMaterial>);
+
+impl FromWorld for HitFlashMaterial {
+    fn from_world(world: &mut World) -> Self {
+        Self(
+            world
+                .resource_mut::<Assets<ColorMaterial>>()
+                .add(Color::srgb(5., 5., 5.)),
+        )
+    }
+}
+
+pub fn knockback_enemies(
+    mut commands: Commands,
+    mut event_reader: EventReader<DamageEvent>,
+    mut enemy_query: Query<(&Enemy, Option<&mut Impulse>), Without<Boss>>,
+) {
+    for event in event_reader.read() {
+        let Some(knockback) = event.knockback else {
+            continue;
+        };
+
+        let Ok((enemy, impulse)) = enemy_query.get_mut(event.entity) else {
+            continue;
+        };
+
+        let push =
+            knockback.direction.normalize_or_zero() * knockback.strength * KNOCKBACK_REFERENCE_SIZE
+                / enemy.size;
+
+        // The enemy may have died from the same hit
+        let mut entity_commands = commands.entity(event.entity);
+
+        match impulse {
+            Some(mut impulse) => impulse.0 += push,
+            None => {
+                entity_commands.try_insert(Impulse(push));
+            }
+        }
+
+        entity_commands.try_insert(HitStun {
+            timer: Timer::from_seconds(HIT_STUN_SECONDS, TimerMode::Once),
+        });
+    }
+}
+
+pub fn flash_on_hit(
+    mut commands: Commands,
+    mut event_reader: EventReader<DamageEvent>,
+    mut query: Query<(&mut MeshMaterial2d<ColorMaterial>, Option<&mut HitFlash>)>,
+    flash_material: Res<HitFlashMaterial>,
+) {
+    for event in event_reader.read() {
+        let Ok((mut material, flash)) = query.get_mut(event.entity) else {
+            continue;
+        };
+
+        match flash {
+            // Already white, the original material is kept
+            Some(mut flash) => flash.timer.reset(),
+            // Hit twice in the same frame, before the flash was inserted
+            None if material.0 == flash_material.0 => {}
+            None => {
+                commands.entity(event.entity).try_insert(HitFlash {
+                    timer: Timer::from_seconds(HIT_FLASH_SECONDS, TimerMode::Once),
+                    original: material.0.clone(),
+                });
+
+                material.0 = flash_material.0.clone();
+            }
+        }
+    }
+}
+
+pub fn recover_from_hits(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut stun_query: Query<(Entity, &mut HitStun)>,
+    mut flash_query: Query<(Entity, &mut HitFlash, &mut MeshMaterial2d<ColorMaterial>)>,
+) {
+    for (entity, mut stun) in stun_query.iter_mut() {
+        if stun.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<HitStun>();
+        }
+    }
+
+    for (entity, mut flash, mut material) in flash_query.iter_mut() {
+        if flash.timer.tick(time.delta()).finished() {
+            material.0 = flash.original.clone();
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
@@ -1,29 +1,80 @@
-use bevy::app::{App, Plugin, Update};
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::AssetApp;
 use bevy::math::Vec2;
-use bevy::prelude::{Component, Entity};
+use bevy::prelude::{Component, IntoSystemConfigs};
+use serde::Deserialize;
 
+pub mod archetype;
+pub mod behaviour;
+pub mod elite;
+pub mod hit;
+pub mod steering;
 mod systems;
 
-use super::health::{DamageEvent, Health2d};
+use super::health::{death_check_listener, DamageEvent, Health2d};
 use crate::quadtree::{Bounds, QuadTree};
+use crate::utils::ron_asset::RonAssetLoader;
+use crate::wave::recycle_stragglers;
+use archetype::*;
+use behaviour::{behave_enemies, Behaviour};
+use elite::{apply_elite_affixes, explode_on_death, summon_minions};
+use hit::{flash_on_hit, knockback_enemies, recover_from_hits, HitFlashMaterial};
+use steering::steer_enemies;
 use systems::*;
 
 #[derive(Component, Clone)]
-#[require(Health2d)]
+#[require(Health2d, Behaviour)]
 pub struct Enemy {
     pub size: f32,
     pub speed: f32,
     pub direction: Vec2,
+    /// Damage dealt to the player on touch.
+    pub contact_damage: f32,
 }
 
+/// Breaks into `count` enemies of `child_archetype` when killed.
+#[derive(Component, Deserialize, Clone, Debug)]
+pub struct SplitOnDeath {
+    pub count: u32,
+    pub child_archetype: String,
+}
+
+/// Velocity added on top of the enemy own movement, it fades out over time.
+#[derive(Component, Default)]
+pub struct Impulse(pub Vec2);
+
+const IMPULSE_DRAG: f32 = 5.;
+
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<DamageEvent>()
-            .add_systems(Update, spawn_enemy_over_time)
+            .init_asset::<EnemyArchetypes>()
+            .register_asset_loader(RonAssetLoader::<EnemyArchetypes>::new(&["archetypes.ron"]))
+            .init_resource::<ArchetypeMeshes>()
+            .init_resource::<HitFlashMaterial>()
+            .add_systems(Startup, load_enemy_archetypes)
+            .add_systems(Update, prepare_archetype_meshes)
             .add_systems(Update, check_enemy_was_shoot)
             .add_systems(Update, move_enemies)
-            .add_systems(Update, follow_player_event_listener);
+            .add_systems(
+                Update,
+                index_enemies
+                    .after(move_enemies)
+                    .after(recycle_stragglers)
+                    .before(steer_enemies)
+                    .before(check_enemy_was_shoot),
+            )
+            .add_systems(Update, apply_impulses.before(move_enemies))
+            .add_systems(Update, split_on_death.before(death_check_listener))
+            .add_systems(Update, behave_enemies.before(steer_enemies))
+            .add_systems(Update, steer_enemies.after(move_enemies))
+            .add_systems(Update, apply_elite_affixes)
+            .add_systems(Update, summon_minions)
+            .add_systems(Update, explode_on_death.before(death_check_listener))
+            .add_systems(Update, knockback_enemies.before(apply_impulses))
+            .add_systems(Update, flash_on_hit)
+            .add_systems(Update, recover_from_hits);
     }
 }
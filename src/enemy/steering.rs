@@ -0,0 +1,169 @@
+use crate::enemy::behaviour::Intent;
+use crate::enemy::Enemy;
+use crate::quadtree::QuadTree;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// How fast enemies turn toward their steering direction.
+const TURN_RATE: f32 = 8.;
+
+/// Weights of each steering force, set per archetype.
+#[derive(Component, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Steering {
+    /// Pull toward the heading the behaviour picked.
+    pub seek: f32,
+    /// Push away from neighbours that are too close.
+    pub separation: f32,
+    /// Match the heading of neighbours.
+    pub alignment: f32,
+    /// Pull toward the centre of the neighbours.
+    pub cohesion: f32,
+    /// Distance, on top of both sizes, in which other enemies count as neighbours.
+    pub radius: f32,
+}
+
+impl Default for Steering {
+    fn default() -> Self {
+        Self {
+            seek: 1.,
+            separation: 1.5,
+            alignment: 0.,
+            cohesion: 0.,
+            radius: 20.,
+        }
+    }
+}
+
+pub struct Neighbour {
+    /// Position relative to the steering enemy.
+    pub offset: Vec2,
+    pub direction: Vec2,
+    /// Distance at which both enemies touch plus the steering radius.
+    pub reach: f32,
+}
+
+impl Steering {
+    /// Direction the enemy wants to go, falls back to seeking when the forces cancel out.
+    pub fn direction(&self, heading: Vec2, neighbours: &[Neighbour]) -> Vec2 {
+        let seek = heading.normalize_or_zero();
+
+        let mut separation = Vec2::ZERO;
+        let mut alignment = Vec2::ZERO;
+        let mut center = Vec2::ZERO;
+        let mut count = 0;
+
+        for neighbour in neighbours {
+            let distance = neighbour.offset.length();
+
+            if distance >= neighbour.reach {
+                continue;
+            }
+
+            // Stronger the more the enemies overlap
+            separation -= neighbour.offset.normalize_or_zero() * (1. - distance / neighbour.reach);
+            alignment += neighbour.direction;
+            center += neighbour.offset;
+            count += 1;
+        }
+
+        if count > 0 {
+            alignment = alignment.normalize_or_zero();
+            center = (center / count as f32).normalize_or_zero();
+        }
+
+        (seek * self.seek
+            + separation * self.separation
+            + alignment * self.alignment
+            + center * self.cohesion)
+            .try_normalize()
+            .unwrap_or(seek)
+    }
+}
+
+pub fn steer_enemies(
+    time: Res<Time>,
+    quad_tree: Res<QuadTree<Entity, Enemy>>,
+    mut enemy_query: Query<(Entity, &mut Enemy, &Transform, &Steering, &Intent)>,
+) {
+    // Neighbours are in reach up to their own size, the search covers the largest one
+    let largest = enemy_query
+        .iter()
+        .map(|(_, enemy, _, _, _)| enemy.size)
+        .fold(0., f32::max);
+
+    let directions: Vec<(Entity, Vec2)> = enemy_query
+        .iter()
+        .map(|(entity, enemy, transform, steering, intent)| {
+            let position = transform.translation.truncate();
+
+            let neighbours: Vec<Neighbour> = quad_tree
+                .query_radius(position, enemy.size + largest + steering.radius)
+                .into_iter()
+                .filter(|other| *other != entity)
+                .filter_map(|other| enemy_query.get(other).ok())
+                .map(|(_, other, other_transform, _, _)| Neighbour {
+                    offset: other_transform.translation.truncate() - position,
+                    direction: other.direction,
+                    reach: enemy.size + other.size + steering.radius,
+                })
+                .collect();
+
+            (entity, steering.direction(intent.heading, &neighbours))
+        })
+        .collect();
+
+    let turn = (TURN_RATE * time.delta_secs()).min(1.);
+
+    for (entity, direction) in directions {
+        if let Ok((_, mut enemy, _, _, _)) = enemy_query.get_mut(entity) {
+            enemy.direction = enemy
+                .direction
+                .lerp(direction, turn)
+                .try_normalize()
+                .unwrap_or(direction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeks_target_without_neighbours() {
+        let direction = Steering::default().direction(Vec2::new(10., 0.), &[]);
+
+        assert_eq!(direction, Vec2::X);
+    }
+
+    #[test]
+    fn separation_pushes_away_from_close_neighbours() {
+        let steering = Steering {
+            seek: 0.,
+            ..Steering::default()
+        };
+        let neighbours = [Neighbour {
+            offset: Vec2::new(5., 0.),
+            direction: Vec2::Y,
+            reach: 20.,
+        }];
+
+        let direction = steering.direction(Vec2::new(0., 10.), &neighbours);
+
+        assert!(direction.x < 0.);
+    }
+
+    #[test]
+    fn far_neighbours_are_ignored() {
+        let neighbours = [Neighbour {
+            offset: Vec2::new(50., 0.),
+            direction: Vec2::X,
+            reach: 20.,
+        }];
+
+        let direction = Steering::default().direction(Vec2::new(0., 10.), &neighbours);
+
+        assert_eq!(direction, Vec2::Y);
+    }
+}
@@ -1,75 +1,97 @@
-use crate::enemy::Enemy;
-use crate::health::DamageEvent;
-use crate::phase::{EnemySpeed, PhaseState, SpawnEnemyEvent};
-use crate::player::player::Player;
-use crate::player::PlayerMovedEvent;
+use crate::enemy::behaviour::Intent;
+use crate::enemy::hit::HitStun;
+use crate::enemy::{Enemy, Impulse, SplitOnDeath, IMPULSE_DRAG};
+use crate::health::status::{ApplyStatusEvent, StatusEffects};
+use crate::health::{DamageCause, DamageEvent, DeathEvent, Knockback};
+use crate::particle::ParticleEmitter;
+use crate::phase::SpawnEnemyEvent;
 use crate::quadtree::QuadTree;
-use crate::shot::Bullet;
+use crate::shot::{Bullet, Hostile};
 use bevy::hierarchy::DespawnRecursiveExt;
 use bevy::log::warn;
 use bevy::prelude::*;
-use bevy::utils::default;
 use rand::random;
+use std::f32::consts::TAU;
 
-pub fn move_enemies(
-    mut quad_tree: ResMut<QuadTree<Entity, Enemy>>,
-    mut enemy_query: Query<(Entity, &mut Enemy, &mut Transform)>,
-    time: Res<Time>,
-) {
-    for (entity, mut enemy, mut transform) in enemy_query.iter_mut() {
-        let sum = enemy.direction * enemy.speed * time.delta_secs();
+type MovingEnemy<'a> = (
+    &'a Enemy,
+    &'a mut Transform,
+    &'a Intent,
+    Has<HitStun>,
+    Option<&'a StatusEffects>,
+);
 
-        let previous_position = transform.translation.clone();
+pub fn move_enemies(mut enemy_query: Query<MovingEnemy>, time: Res<Time>) {
+    for (enemy, mut transform, intent, stunned, effects) in enemy_query.iter_mut() {
+        // Stunned enemies only move with their knockback
+        let speed_multiplier = if stunned {
+            0.
+        } else {
+            intent.speed_multiplier * effects.map_or(1., StatusEffects::speed_multiplier)
+        };
+        let sum = enemy.direction * enemy.speed * speed_multiplier * time.delta_secs();
 
         transform.translation += Transform::from_xyz(sum.x, sum.y, 0.).translation;
-
-        let _ = quad_tree.insert(entity, enemy.clone(), transform.translation.truncate());
     }
 }
 
-pub fn spawn_enemy_over_time(
-    time: Res<Time>,
-    mut state: ResMut<PhaseState>,
-    mut spawn_enemy_event_writter: EventWriter<SpawnEnemyEvent>,
+/// Rebuilds the tree from scratch once everything moved this frame, so entries never go
+/// stale whatever moved the enemy: walking, impulses, knockback or recycling.
+pub fn index_enemies(
+    mut quad_tree: ResMut<QuadTree<Entity, Enemy>>,
+    enemy_query: Query<(Entity, &Enemy, &Transform)>,
 ) {
-    if state.last_enemy_spawn_in_seconds < state.enemy_spawn_time {
-        state.last_enemy_spawn_in_seconds += time.delta_secs();
-        return;
-    };
+    quad_tree.clear();
 
-    let x = random::<f64>() + (state.start_at.elapsed().as_secs_f64()).log10();
-
-    let speed = match x {
-        x if x < 0.3 => EnemySpeed::RandomSlow,
-        x if x < 0.8 => EnemySpeed::RandomNormal,
-        _ => EnemySpeed::RandomFast,
-    };
+    for (entity, enemy, transform) in enemy_query.iter() {
+        quad_tree.insert(entity, enemy.clone(), transform.translation.truncate());
+    }
+}
 
-    spawn_enemy_event_writter.send(SpawnEnemyEvent { speed, ..default() });
+pub fn apply_impulses(time: Res<Time>, mut query: Query<(&mut Impulse, &mut Transform)>) {
+    for (mut impulse, mut transform) in query.iter_mut() {
+        transform.translation += (impulse.0 * time.delta_secs()).extend(0.);
+        impulse.0 *= (-IMPULSE_DRAG * time.delta_secs()).exp();
+    }
 }
 
-pub fn follow_player_event_listener(
-    mut events: EventReader<PlayerMovedEvent>,
-    mut snake_query: Query<&Transform, With<Player>>,
-    mut enemy_query: Query<(&mut Enemy, &Transform), With<Enemy>>,
+/// Speed the split children are thrown outward with.
+const SPLIT_SPEED: f32 = 300.;
+
+pub fn split_on_death(
+    mut event_reader: EventReader<DeathEvent>,
+    enemy_query: Query<(&SplitOnDeath, &Enemy)>,
+    mut spawn_enemy_event_writter: EventWriter<SpawnEnemyEvent>,
 ) {
-    for _ in events.read() {
-        if let Ok(snake_transform) = snake_query.get_single_mut() {
-            for (mut enemy, enemy_transform) in enemy_query.iter_mut() {
-                let direction =
-                    (snake_transform.translation - enemy_transform.translation).normalize();
+    for event in event_reader.read() {
+        let Ok((split, enemy)) = enemy_query.get(event.entity) else {
+            continue;
+        };
 
-                enemy.direction = direction.truncate();
-            }
+        let offset = random::<f32>() * TAU;
+
+        for index in 0..split.count {
+            let angle = offset + TAU * index as f32 / split.count as f32;
+            let direction = Vec2::from_angle(angle);
+
+            spawn_enemy_event_writter.send(SpawnEnemyEvent {
+                position: Some(event.position + (direction * enemy.size * 0.5).extend(0.)),
+                impulse: direction * SPLIT_SPEED,
+                ..SpawnEnemyEvent::new(&split.child_archetype)
+            });
         }
     }
 }
 
+/// Speed the enemies are pushed back with when shot.
+const BULLET_KNOCKBACK: f32 = 150.;
+
 pub fn check_enemy_was_shoot(
     mut commands: Commands,
     mut enemy_query: Query<(Entity, &Enemy, &Transform)>,
-    mut bullet_query: Query<(Entity, &Transform, &Bullet)>,
+    mut bullet_query: Query<(Entity, &Transform, &Bullet), Without<Hostile>>,
     mut event_writer: EventWriter<DamageEvent>,
+    mut status_writer: EventWriter<ApplyStatusEvent>,
     mut quad_tree: ResMut<QuadTree<Entity, Enemy>>,
 ) {
     for (bullet_entity, bullet_transform, bullet) in bullet_query.iter_mut() {
@@ -90,7 +112,28 @@ pub fn check_enemy_was_shoot(
                         event_writer.send(DamageEvent {
                             entity: enemy_entity,
                             damage: bullet.damage,
+                            damage_type: bullet.damage_type,
+                            source: bullet.source,
+                            cause: DamageCause::Attack,
+                            unmitigated: false,
+                            knockback: Some(Knockback {
+                                direction: bullet.direction,
+                                strength: BULLET_KNOCKBACK,
+                            }),
                         });
+
+                        status_writer.send_batch(bullet.on_hit.iter().map(|effect| {
+                            ApplyStatusEvent {
+                                entity: enemy_entity,
+                                effect: *effect,
+                                source: bullet.source,
+                            }
+                        }));
+
+                        commands.spawn((
+                            ParticleEmitter::hit_sparks(-bullet.direction),
+                            *bullet_transform,
+                        ));
                     }
                 }
             }
@@ -0,0 +1,91 @@
+use crate::boss::Boss;
+use crate::enemy::Enemy;
+use crate::health::Health2d;
+use crate::player::player::Player;
+use bevy::color::Color;
+use bevy::math::Vec2;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+const BAR_HEIGHT: f32 = 3.;
+const BAR_MARGIN: f32 = 4.;
+const DEFAULT_OWNER_SIZE: f32 = 10.;
+
+/// Root of the bar spawned as a child of every entity with [`Health2d`], the player's and
+/// bosses' health is shown by the HUD and the boss bar instead.
+#[derive(Component)]
+pub struct HealthBar {
+    fill: Entity,
+}
+
+#[derive(Component)]
+pub struct HealthBarFill;
+
+pub fn spawn_health_bars(
+    mut commands: Commands,
+    health_query: Query<(Entity, Option<&Enemy>), Added<Health2d>>,
+    player_query: Query<(), With<Player>>,
+    boss_query: Query<(), With<Boss>>,
+) {
+    for (entity, enemy) in health_query.iter() {
+        if player_query.contains(entity) || boss_query.contains(entity) {
+            continue;
+        }
+
+        let size = enemy.map_or(DEFAULT_OWNER_SIZE, |enemy| enemy.size);
+        let width = size * 2.;
+
+        let fill = commands
+            .spawn((
+                HealthBarFill,
+                Sprite {
+                    color: Color::srgb(2., 0.2, 0.2),
+                    custom_size: Some(Vec2::new(width, BAR_HEIGHT)),
+                    anchor: Anchor::CenterLeft,
+                    ..default()
+                },
+                Transform::from_xyz(-width / 2., 0., 0.1),
+            ))
+            .id();
+
+        let bar = commands
+            .spawn((
+                HealthBar { fill },
+                Sprite {
+                    color: Color::srgba(0., 0., 0., 0.6),
+                    custom_size: Some(Vec2::new(width, BAR_HEIGHT)),
+                    ..default()
+                },
+                Transform::from_xyz(0., size + BAR_MARGIN, 1.),
+                Visibility::Hidden,
+            ))
+            .add_child(fill)
+            .id();
+
+        commands.entity(entity).add_child(bar);
+    }
+}
+
+pub fn update_health_bars(
+    health_query: Query<(&Health2d, &Children), Changed<Health2d>>,
+    mut bar_query: Query<(&HealthBar, &mut Visibility)>,
+    mut fill_query: Query<&mut Transform, With<HealthBarFill>>,
+) {
+    for (health, children) in health_query.iter() {
+        for child in children.iter() {
+            let Ok((bar, mut visibility)) = bar_query.get_mut(*child) else {
+                continue;
+            };
+
+            *visibility = if health.health >= health.max_health {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+
+            if let Ok(mut fill_transform) = fill_query.get_mut(bar.fill) {
+                fill_transform.scale.x = (health.health / health.max_health).clamp(0., 1.);
+            }
+        }
+    }
+}
@@ -1,34 +1,213 @@
 use crate::enemy::Enemy;
+use crate::particle::ParticleEmitter;
+use crate::pickup::drops::{CurrentDropTables, Drops};
+use crate::pickup::{PickupKind, SpawnPickupEvent};
+use crate::player::player::Player;
 use crate::player::PlayerReceiveXpEvent;
 use crate::quadtree::QuadTree;
-use crate::timefade::{MoveAndFade, TimeFadePlugin};
+use crate::timefade::TimeFadePlugin;
 use crate::utils::random_direction;
+use bar::{spawn_health_bars, update_health_bars};
 use bevy::app::{App, Plugin, Update};
-use bevy::color::Color;
 use bevy::hierarchy::DespawnRecursiveExt;
 use bevy::log::warn;
 use bevy::prelude::*;
-use rand::{random, Rng};
+use serde::Deserialize;
+use status::{
+    apply_status_listener, tick_status_effects, update_status_tints, ApplyStatusEvent,
+    StatusTintAssets,
+};
+use std::collections::HashMap;
 
+mod bar;
+pub mod status;
+
+/// Armour never brings a hit below this fraction of its damage.
+const MIN_ARMOURED_DAMAGE: f32 = 0.2;
+const SHIELD_RECHARGE_DELAY: f32 = 3.;
+/// Seconds for an empty shield to recharge fully once the delay is over.
+const SHIELD_RECHARGE_SECONDS: f32 = 2.;
+
+/// Sent once when an entity other than the player runs out of health, before it is
+/// despawned.
 #[derive(Event)]
 pub struct DeathEvent {
-    entity: Entity,
+    pub entity: Entity,
+    /// Source of the killing blow, which may have been despawned since.
+    pub killer: Option<Entity>,
+    pub cause: DamageCause,
+    pub damage_type: DamageType,
+    pub position: Vec3,
+}
+
+/// Marks entities whose [`DeathEvent`] was already sent.
+#[derive(Component)]
+pub struct Dead;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum DamageType {
+    #[default]
+    Kinetic,
+    Fire,
+    Shock,
+    Poison,
+}
+
+/// What kind of thing dealt a hit, independent of which entity it came from.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum DamageCause {
+    /// Weapons, attacks and status effects of the source.
+    #[default]
+    Attack,
+    /// A pickup collected by the player, like the bomb.
+    Pickup,
 }
 
+/// Raw damage before critical hits and mitigation.
 #[derive(Event)]
 pub struct DamageEvent {
     pub entity: Entity,
     pub damage: f32,
+    pub damage_type: DamageType,
+    /// What dealt the damage, its [`CriticalStrike`] is rolled for this hit.
+    pub source: Option<Entity>,
+    pub cause: DamageCause,
+    /// Skips critical hits, resistances, armour and shields, for damage that must land in
+    /// full like the bomb.
+    pub unmitigated: bool,
+    pub knockback: Option<Knockback>,
+}
+
+/// Damage after critical hits, resistances, armour and shields, sent for each applied
+/// [`DamageEvent`].
+#[derive(Event)]
+pub struct DamageDealtEvent {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+    /// Damage taken by the shield and the health pool together.
+    pub damage: f32,
+    /// Part of `damage` taken by the shield.
+    pub absorbed: f32,
+    pub damage_type: DamageType,
+    pub critical: bool,
+}
+
+/// Latest damage an entity took, reported as the killing blow when it dies.
+#[derive(Clone, Copy, Debug)]
+pub struct LastHit {
+    pub source: Option<Entity>,
+    pub cause: DamageCause,
+    pub damage_type: DamageType,
+}
+
+/// Push applied to the victim of a hit, away from the source.
+#[derive(Clone, Copy, Debug)]
+pub struct Knockback {
+    pub direction: Vec2,
+    pub strength: f32,
 }
 
 #[derive(Component)]
 pub struct Health2d {
     pub max_health: f32,
     pub health: f32,
+    /// Absorbs damage before the health pool.
+    pub shield: Option<Shield>,
+    /// Subtracted from every hit, after resistances.
+    pub armour: f32,
+    /// Health restored every second, up to the max health.
+    pub regeneration: f32,
+    pub last_hit: Option<LastHit>,
 
     pub xp_on_death: u32,
 }
 
+/// Fraction of each damage type ignored, negative values take extra damage.
+#[derive(Component, Deserialize, Clone, Default, Debug)]
+pub struct Resistances(pub HashMap<DamageType, f32>);
+
+impl Resistances {
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        (1. - self.0.get(&damage_type).copied().unwrap_or(0.)).max(0.)
+    }
+}
+
+/// Chance for the damage dealt by the entity to be multiplied.
+#[derive(Component)]
+pub struct CriticalStrike {
+    /// Between 0 and 1.
+    pub chance: f32,
+    pub multiplier: f32,
+}
+
+/// Applies the critical strike of the source and the resistances of the victim, `roll` is
+/// a random value between 0 and 1.
+pub fn resolve_damage(
+    damage: f32,
+    damage_type: DamageType,
+    critical_strike: Option<&CriticalStrike>,
+    resistances: Option<&Resistances>,
+    roll: f32,
+) -> (f32, bool) {
+    let critical = critical_strike.is_some_and(|critical_strike| roll < critical_strike.chance);
+    let multiplier = match critical_strike {
+        Some(critical_strike) if critical => critical_strike.multiplier,
+        _ => 1.,
+    };
+    let resistance = resistances.map_or(1., |resistances| resistances.multiplier(damage_type));
+
+    (damage * multiplier * resistance, critical)
+}
+
+/// Absorbs damage before it reaches the health pool, recharging once the entity has not been
+/// hit for a while.
+pub struct Shield {
+    pub max: f32,
+    pub amount: f32,
+    /// Seconds without damage before the shield recharges.
+    pub recharge_delay: f32,
+    /// Shield restored every second while recharging.
+    pub recharge_rate: f32,
+    since_hit: f32,
+}
+
+impl Shield {
+    pub fn new(max: f32) -> Self {
+        Self {
+            max,
+            amount: max,
+            recharge_delay: SHIELD_RECHARGE_DELAY,
+            recharge_rate: max / SHIELD_RECHARGE_SECONDS,
+            since_hit: 0.,
+        }
+    }
+
+    /// Raising the max fills the shield by the same amount, like the max health.
+    pub fn set_max(&mut self, max: f32) {
+        self.amount = (self.amount + max - self.max).clamp(0., max);
+        self.recharge_rate = max / SHIELD_RECHARGE_SECONDS;
+        self.max = max;
+    }
+
+    /// Returns the part of `damage` taken by the shield.
+    fn absorb(&mut self, damage: f32) -> f32 {
+        self.since_hit = 0.;
+
+        let absorbed = damage.min(self.amount);
+        self.amount -= absorbed;
+
+        absorbed
+    }
+
+    fn recharge(&mut self, delta: f32) {
+        self.since_hit += delta;
+
+        if self.since_hit >= self.recharge_delay {
+            self.amount = (self.amount + self.recharge_rate * delta).min(self.max);
+        }
+    }
+}
+
 impl Health2d {
     pub fn full_health(v: f32) -> Self {
         Self {
@@ -37,6 +216,40 @@ impl Health2d {
             ..Self::default()
         }
     }
+
+    /// Applies the armour then the shield, returns the damage taken by the shield and the
+    /// health pool together and the part absorbed by the shield.
+    pub fn take_damage(&mut self, damage: f32) -> (f32, f32) {
+        let damage = (damage - self.armour).max(damage * MIN_ARMOURED_DAMAGE);
+        let absorbed = self
+            .shield
+            .as_mut()
+            .map_or(0., |shield| shield.absorb(damage));
+
+        self.health -= damage - absorbed;
+
+        (damage, absorbed)
+    }
+
+    /// Health and shield left.
+    fn amounts(&self) -> (f32, f32) {
+        let shield = self.shield.as_ref().map_or(0., |shield| shield.amount);
+
+        (self.health, shield)
+    }
+
+    pub fn regenerate(&mut self, delta: f32) {
+        // Dead entities stay dead until they are despawned
+        if self.health <= 0. {
+            return;
+        }
+
+        self.health = (self.health + self.regeneration * delta).min(self.max_health);
+
+        if let Some(shield) = self.shield.as_mut() {
+            shield.recharge(delta);
+        }
+    }
 }
 
 impl Default for Health2d {
@@ -44,6 +257,10 @@ impl Default for Health2d {
         Self {
             max_health: 5.,
             health: 5.,
+            shield: None,
+            armour: 0.,
+            regeneration: 0.,
+            last_hit: None,
             xp_on_death: 1,
         }
     }
@@ -54,74 +271,211 @@ pub struct HealthPlugin;
 impl Plugin for HealthPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<DeathEvent>()
+            .add_event::<DamageDealtEvent>()
+            .add_event::<ApplyStatusEvent>()
+            .init_resource::<StatusTintAssets>()
             .add_event::<PlayerReceiveXpEvent>()
             .add_plugins(TimeFadePlugin)
             .add_systems(Update, death_check)
             .add_systems(Update, damage_listener)
-            .add_systems(Update, death_check_listener);
+            .add_systems(Update, regenerate_health.before(damage_listener))
+            .add_systems(Update, death_check_listener)
+            .add_systems(Update, apply_status_listener)
+            .add_systems(Update, tick_status_effects.before(damage_listener))
+            .add_systems(Update, update_status_tints.before(death_check_listener))
+            .add_systems(Update, spawn_health_bars)
+            .add_systems(Update, update_health_bars.after(damage_listener));
     }
 }
 
 pub fn damage_listener(
     mut event_reader: EventReader<DamageEvent>,
-    mut query: Query<&mut Health2d>,
+    mut query: Query<(&mut Health2d, Option<&Resistances>)>,
+    source_query: Query<&CriticalStrike>,
+    mut dealt_writer: EventWriter<DamageDealtEvent>,
 ) {
     for event in event_reader.read() {
-        if let Ok(mut entity) = query.get_mut(event.entity) {
-            entity.health = entity.health - event.damage;
+        if let Ok((mut entity, resistances)) = query.get_mut(event.entity) {
+            let (damage, absorbed, critical) = if event.unmitigated {
+                entity.health -= event.damage;
+
+                (event.damage, 0., false)
+            } else {
+                let critical_strike = event
+                    .source
+                    .and_then(|source| source_query.get(source).ok());
+
+                let (damage, critical) = resolve_damage(
+                    event.damage,
+                    event.damage_type,
+                    critical_strike,
+                    resistances,
+                    rand::random(),
+                );
+
+                let (damage, absorbed) = entity.take_damage(damage);
+
+                (damage, absorbed, critical)
+            };
+
+            entity.last_hit = Some(LastHit {
+                source: event.source,
+                cause: event.cause,
+                damage_type: event.damage_type,
+            });
+
+            dealt_writer.send(DamageDealtEvent {
+                entity: event.entity,
+                source: event.source,
+                damage,
+                absorbed,
+                damage_type: event.damage_type,
+                critical,
+            });
         } else {
             warn!("Entity not found for damage");
         }
     }
 }
 
+/// Only flags the health as changed when a value moves, the health bars update on changes.
+pub fn regenerate_health(time: Res<Time>, mut query: Query<&mut Health2d>) {
+    for mut health in query.iter_mut() {
+        let before = health.amounts();
+
+        health
+            .bypass_change_detection()
+            .regenerate(time.delta_secs());
+
+        if health.amounts() != before {
+            health.set_changed();
+        }
+    }
+}
+
+type DyingFilter = (Without<Player>, Without<Dead>);
+
+/// The player is left out, its death ends the phase instead of despawning it.
 pub fn death_check(
+    mut commands: Commands,
     mut event_writer: EventWriter<DeathEvent>,
-    health_query: Query<(Entity, &Health2d)>,
+    health_query: Query<(Entity, &Health2d, &Transform), DyingFilter>,
 ) {
-    for (entity, health) in health_query.iter() {
-        if health.health <= 0. {
-            event_writer.send(DeathEvent {
-                entity: entity.clone(),
-            });
+    for (entity, health, transform) in health_query.iter() {
+        if health.health > 0. {
+            continue;
         }
+
+        // The despawn is applied later, the marker keeps the event from being sent again
+        commands.entity(entity).try_insert(Dead);
+
+        let last_hit = health.last_hit;
+
+        event_writer.send(DeathEvent {
+            entity,
+            killer: last_hit.and_then(|last_hit| last_hit.source),
+            cause: last_hit.map_or_else(DamageCause::default, |last_hit| last_hit.cause),
+            damage_type: last_hit.map_or_else(DamageType::default, |last_hit| last_hit.damage_type),
+            position: transform.translation,
+        });
     }
 }
 
 pub fn death_check_listener(
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
     mut commands: Commands,
     mut event_reader: EventReader<DeathEvent>,
-    mut xp_writer: EventWriter<PlayerReceiveXpEvent>,
-    health_query: Query<(&Health2d, &Transform)>,
+    mut pickup_writer: EventWriter<SpawnPickupEvent>,
+    health_query: Query<(&Health2d, Option<&Drops>)>,
     mut quad_tree: ResMut<QuadTree<Entity, Enemy>>,
+    drop_tables: CurrentDropTables,
 ) {
     for event in event_reader.read() {
         if let Some(entity) = commands.get_entity(event.entity) {
-            if let Ok((health, transform)) = health_query.get(event.entity) {
+            if let Ok((health, drops)) = health_query.get(event.entity) {
                 entity.try_despawn_recursive();
 
-                quad_tree.delete(event.entity, transform.translation.truncate());
-
-                xp_writer.send(PlayerReceiveXpEvent {
-                    xp: health.xp_on_death,
-                });
-
-                (0..rand::thread_rng().gen_range(7..20)).for_each(|_| {
-                    commands.spawn((
-                        MoveAndFade {
-                            speed: rand::thread_rng().gen_range(350..450) as f32,
-                            direction: random_direction(),
-                            deceleration: rand::thread_rng().gen_range(5..10) as f32,
-                            timer: Timer::from_seconds(random::<f32>() + 0.3, TimerMode::Once),
-                        },
-                        transform.clone(),
-                        Mesh2d(meshes.add(Circle::new(1.))),
-                        MeshMaterial2d(materials.add(Color::srgb(7.5, 7.5, 0.))),
-                    ));
-                });
+                quad_tree.delete(event.entity, event.position.truncate());
+
+                if health.xp_on_death > 0 {
+                    pickup_writer.send(SpawnPickupEvent {
+                        kind: PickupKind::Xp(health.xp_on_death),
+                        position: event.position,
+                    });
+                }
+
+                let items = drops
+                    .and_then(|drops| drop_tables.get(&drops.0))
+                    .map(|table| table.roll(&mut rand::thread_rng()))
+                    .unwrap_or_default();
+
+                for kind in items {
+                    // Spread the items so they do not stack on top of the gem
+                    let offset = random_direction() * rand::random::<f32>() * 16.;
+
+                    pickup_writer.send(SpawnPickupEvent {
+                        kind,
+                        position: event.position + offset.extend(0.),
+                    });
+                }
+
+                commands.spawn((
+                    ParticleEmitter::death_burst(),
+                    Transform::from_translation(event.position),
+                ));
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn critical_hits_and_resistances_multiply() {
+        let critical_strike = CriticalStrike {
+            chance: 0.5,
+            multiplier: 2.,
+        };
+        let resistances = Resistances(HashMap::from([
+            (DamageType::Fire, 0.5),
+            (DamageType::Poison, -1.),
+        ]));
+
+        let resolve = |damage_type, roll| {
+            resolve_damage(
+                10.,
+                damage_type,
+                Some(&critical_strike),
+                Some(&resistances),
+                roll,
+            )
+        };
+
+        assert_eq!(resolve(DamageType::Kinetic, 0.9), (10., false));
+        assert_eq!(resolve(DamageType::Kinetic, 0.1), (20., true));
+        assert_eq!(resolve(DamageType::Fire, 0.9), (5., false));
+        assert_eq!(resolve(DamageType::Poison, 0.9), (20., false));
+    }
+
+    #[test]
+    fn armour_then_shield_take_the_hit() {
+        let mut health = Health2d {
+            shield: Some(Shield::new(5.)),
+            armour: 2.,
+            ..Health2d::full_health(20.)
+        };
+
+        assert_eq!(health.take_damage(10.), (8., 5.));
+        assert_eq!(health.health, 17.);
+
+        // Armour keeps a fraction of weak hits
+        assert_eq!(health.take_damage(1.), (MIN_ARMOURED_DAMAGE, 0.));
+
+        health.regenerate(SHIELD_RECHARGE_DELAY / 2.);
+        assert_eq!(health.shield.as_ref().unwrap().amount, 0.);
+
+        health.regenerate(SHIELD_RECHARGE_DELAY);
+        assert_eq!(health.shield.as_ref().unwrap().amount, 5.);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::enemy::Enemy;
+use crate::health::{DamageCause, DamageEvent, DamageType};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Seconds between two damage ticks of burn and poison.
+const TICK_INTERVAL: f32 = 0.5;
+const MAX_POISON_STACKS: usize = 5;
+/// Scale of the tint overlay relative to the enemy size.
+const TINT_SCALE: f32 = 1.05;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum StatusKind {
+    /// Fire damage over time, a new burn refreshes the current one.
+    Burn,
+    /// Poison damage over time, every application stacks up to a limit.
+    Poison,
+    /// Slows movement by the potency fraction, the strongest slow wins.
+    Slow,
+    /// Stops movement.
+    Freeze,
+    /// Stops movement and actions, like shooting.
+    Stun,
+}
+
+impl StatusKind {
+    fn damage_type(self) -> Option<DamageType> {
+        match self {
+            StatusKind::Burn => Some(DamageType::Fire),
+            StatusKind::Poison => Some(DamageType::Poison),
+            _ => None,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            StatusKind::Burn => Color::srgba(4., 1., 0., 0.4),
+            StatusKind::Poison => Color::srgba(0.5, 3., 0.2, 0.4),
+            StatusKind::Slow => Color::srgba(0.3, 0.8, 2., 0.3),
+            StatusKind::Freeze => Color::srgba(1.5, 2.5, 4., 0.6),
+            StatusKind::Stun => Color::srgba(3., 3., 0.5, 0.4),
+        }
+    }
+}
+
+/// Effect a weapon applies on hit.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Damage per second for burn and poison, slowed fraction for slow, unused otherwise.
+    #[serde(default)]
+    pub potency: f32,
+    pub seconds: f32,
+    /// Between 0 and 1, rolled on every hit.
+    #[serde(default = "always")]
+    pub chance: f32,
+}
+
+fn always() -> f32 {
+    1.
+}
+
+#[derive(Event)]
+pub struct ApplyStatusEvent {
+    pub entity: Entity,
+    pub effect: StatusEffect,
+    pub source: Option<Entity>,
+}
+
+struct ActiveStatus {
+    kind: StatusKind,
+    potency: f32,
+    timer: Timer,
+    source: Option<Entity>,
+}
+
+/// Effects currently affecting the entity.
+#[derive(Component, Default)]
+pub struct StatusEffects {
+    active: Vec<ActiveStatus>,
+    tick: Timer,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect, source: Option<Entity>) {
+        let status = ActiveStatus {
+            kind: effect.kind,
+            potency: effect.potency,
+            timer: Timer::from_seconds(effect.seconds, TimerMode::Once),
+            source,
+        };
+
+        if effect.kind == StatusKind::Poison {
+            let stacks = self.count(StatusKind::Poison);
+
+            if stacks < MAX_POISON_STACKS {
+                self.active.push(status);
+            } else if let Some(oldest) = self
+                .active
+                .iter_mut()
+                .filter(|active| active.kind == StatusKind::Poison)
+                .min_by(|a, b| a.timer.remaining().cmp(&b.timer.remaining()))
+            {
+                // Full stacks replace the one closest to running out
+                *oldest = status;
+            }
+
+            return;
+        }
+
+        // Every other kind refreshes the current effect, keeping the strongest potency and the
+        // longest duration
+        match self
+            .active
+            .iter_mut()
+            .find(|active| active.kind == effect.kind)
+        {
+            Some(active) => {
+                active.potency = active.potency.max(status.potency);
+                if status.timer.remaining() > active.timer.remaining() {
+                    active.timer = status.timer;
+                }
+                active.source = status.source;
+            }
+            None => self.active.push(status),
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.active.iter().any(|active| active.kind == kind)
+    }
+
+    pub fn count(&self, kind: StatusKind) -> usize {
+        self.active
+            .iter()
+            .filter(|active| active.kind == kind)
+            .count()
+    }
+
+    /// Multiplies the movement speed, between 0 and 1.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.has(StatusKind::Freeze) || self.has(StatusKind::Stun) {
+            return 0.;
+        }
+
+        let slow = self
+            .active
+            .iter()
+            .filter(|active| active.kind == StatusKind::Slow)
+            .map(|active| active.potency)
+            .fold(0., f32::max);
+
+        (1. - slow).clamp(0., 1.)
+    }
+
+    /// Whether the entity can do anything besides moving, like shooting.
+    pub fn can_act(&self) -> bool {
+        !self.has(StatusKind::Stun)
+    }
+
+    /// The most noticeable effect, shown as a tint.
+    pub fn tint(&self) -> Option<StatusKind> {
+        [
+            StatusKind::Freeze,
+            StatusKind::Stun,
+            StatusKind::Burn,
+            StatusKind::Poison,
+            StatusKind::Slow,
+        ]
+        .into_iter()
+        .find(|kind| self.has(*kind))
+    }
+
+    /// Advances the effects and returns the damage dealt by the ticks that happened, grouped
+    /// by kind and source.
+    fn tick(&mut self, delta: Duration) -> Vec<(StatusKind, Option<Entity>, f32)> {
+        if self.tick.duration().is_zero() {
+            self.tick = Timer::from_seconds(TICK_INTERVAL, TimerMode::Repeating);
+        }
+
+        let ticks = self.tick.tick(delta).times_finished_this_tick() as f32;
+        let mut damage: Vec<(StatusKind, Option<Entity>, f32)> = Vec::new();
+
+        for active in self.active.iter_mut() {
+            active.timer.tick(delta);
+
+            if ticks == 0. || active.kind.damage_type().is_none() {
+                continue;
+            }
+
+            let amount = active.potency * TICK_INTERVAL * ticks;
+
+            match damage
+                .iter_mut()
+                .find(|(kind, source, _)| *kind == active.kind && *source == active.source)
+            {
+                Some((_, _, total)) => *total += amount,
+                None => damage.push((active.kind, active.source, amount)),
+            }
+        }
+
+        self.active.retain(|active| !active.timer.finished());
+
+        damage
+    }
+}
+
+/// Overlay child showing the current [`StatusEffects::tint`].
+#[derive(Component)]
+pub struct StatusTint {
+    kind: StatusKind,
+    overlay: Entity,
+}
+
+#[derive(Resource)]
+pub struct StatusTintAssets {
+    mesh: Handle<Mesh>,
+    materials: HashMap<StatusKind, Handle<ColorMaterial>>,
+}
+
+impl FromWorld for StatusTintAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Circle::new(1.));
+
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let materials = [
+            StatusKind::Burn,
+            StatusKind::Poison,
+            StatusKind::Slow,
+            StatusKind::Freeze,
+            StatusKind::Stun,
+        ]
+        .into_iter()
+        .map(|kind| (kind, materials.add(kind.color())))
+        .collect();
+
+        Self { mesh, materials }
+    }
+}
+
+pub fn apply_status_listener(
+    mut commands: Commands,
+    mut event_reader: EventReader<ApplyStatusEvent>,
+    mut query: Query<Option<&mut StatusEffects>>,
+) {
+    // Effects for entities without the component yet, inserted together
+    let mut new_effects: HashMap<Entity, StatusEffects> = HashMap::new();
+
+    for event in event_reader.read() {
+        if rand::random::<f32>() >= event.effect.chance {
+            continue;
+        }
+
+        match query.get_mut(event.entity) {
+            Ok(Some(mut effects)) => effects.apply(event.effect, event.source),
+            Ok(None) => new_effects
+                .entry(event.entity)
+                .or_default()
+                .apply(event.effect, event.source),
+            Err(_) => {}
+        }
+    }
+
+    for (entity, effects) in new_effects {
+        commands.entity(entity).try_insert(effects);
+    }
+}
+
+pub fn tick_status_effects(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut StatusEffects)>,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    for (entity, mut effects) in query.iter_mut() {
+        for (kind, source, damage) in effects.tick(time.delta()) {
+            let Some(damage_type) = kind.damage_type() else {
+                continue;
+            };
+
+            damage_writer.send(DamageEvent {
+                entity,
+                damage,
+                damage_type,
+                source,
+                cause: DamageCause::Attack,
+                unmitigated: false,
+                knockback: None,
+            });
+        }
+    }
+}
+
+pub fn update_status_tints(
+    mut commands: Commands,
+    query: Query<(Entity, &StatusEffects, &Enemy, Option<&StatusTint>)>,
+    assets: Res<StatusTintAssets>,
+) {
+    for (entity, effects, enemy, tint) in query.iter() {
+        let kind = effects.tint();
+
+        if tint.map(|tint| tint.kind) == kind {
+            continue;
+        }
+
+        if let Some(tint) = tint {
+            commands.entity(tint.overlay).despawn_recursive();
+            commands.entity(entity).remove::<StatusTint>();
+        }
+
+        let Some(kind) = kind else {
+            continue;
+        };
+
+        let overlay = commands
+            .spawn((
+                Mesh2d(assets.mesh.clone()),
+                MeshMaterial2d(assets.materials[&kind].clone()),
+                Transform::from_xyz(0., 0., 0.05).with_scale(Vec3::splat(enemy.size * TINT_SCALE)),
+            ))
+            .id();
+
+        commands
+            .entity(entity)
+            .add_child(overlay)
+            .insert(StatusTint { kind, overlay });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(kind: StatusKind, potency: f32, seconds: f32) -> StatusEffect {
+        StatusEffect {
+            kind,
+            potency,
+            seconds,
+            chance: 1.,
+        }
+    }
+
+    #[test]
+    fn poison_stacks_and_burn_refreshes() {
+        let mut effects = StatusEffects::default();
+
+        for _ in 0..MAX_POISON_STACKS + 2 {
+            effects.apply(effect(StatusKind::Poison, 1., 2.), None);
+            effects.apply(effect(StatusKind::Burn, 1., 2.), None);
+        }
+
+        assert_eq!(effects.count(StatusKind::Poison), MAX_POISON_STACKS);
+        assert_eq!(effects.count(StatusKind::Burn), 1);
+
+        let damage = effects.tick(Duration::from_secs_f32(TICK_INTERVAL));
+
+        assert!(damage.contains(&(StatusKind::Poison, None, MAX_POISON_STACKS as f32 * 0.5)));
+        assert!(damage.contains(&(StatusKind::Burn, None, 0.5)));
+    }
+
+    #[test]
+    fn movement_follows_the_strongest_effect() {
+        let mut effects = StatusEffects::default();
+
+        effects.apply(effect(StatusKind::Slow, 0.3, 2.), None);
+        effects.apply(effect(StatusKind::Slow, 0.5, 1.), None);
+        assert_eq!(effects.speed_multiplier(), 0.5);
+
+        effects.apply(effect(StatusKind::Freeze, 0., 1.), None);
+        assert_eq!(effects.speed_multiplier(), 0.);
+
+        effects.tick(Duration::from_secs_f32(1.5));
+        assert!(!effects.has(StatusKind::Freeze));
+        assert_eq!(effects.speed_multiplier(), 0.5);
+        assert_eq!(effects.tint(), Some(StatusKind::Slow));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::health::Health2d;
+use crate::phase::PhaseState;
+use crate::player::player::Player;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::color::Color;
+use bevy::prelude::*;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hud)
+            .add_systems(Update, update_hud_bars)
+            .add_systems(Update, update_hud_text);
+    }
+}
+
+#[derive(Component, PartialEq)]
+pub enum HudBar {
+    Health,
+    Shield,
+    Xp,
+}
+
+#[derive(Component, PartialEq)]
+pub enum HudText {
+    Health,
+    Level,
+    Timer,
+    Kills,
+}
+
+const BAR_WIDTH: f32 = 240.;
+
+fn spawn_bar(parent: &mut ChildBuilder, bar: HudBar, height: f32, color: Color) {
+    parent
+        .spawn((
+            Node {
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(height),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+        ))
+        .with_children(|background| {
+            background.spawn((
+                bar,
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                BackgroundColor(color),
+            ));
+        });
+}
+
+fn spawn_text(parent: &mut ChildBuilder, text: HudText) {
+    parent.spawn((
+        text,
+        Text::new(""),
+        TextFont {
+            font_size: 16.,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+pub fn spawn_hud(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(16.),
+            bottom: Val::Px(16.),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(6.),
+            ..default()
+        })
+        .with_children(|hud| {
+            hud.spawn(Node {
+                column_gap: Val::Px(8.),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_bar(row, HudBar::Health, 14., Color::srgb(0.85, 0.15, 0.2));
+                spawn_text(row, HudText::Health);
+            });
+
+            hud.spawn(Node::default()).with_children(|row| {
+                spawn_bar(row, HudBar::Shield, 4., Color::srgb(0.3, 0.7, 1.));
+            });
+
+            hud.spawn(Node {
+                column_gap: Val::Px(8.),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_bar(row, HudBar::Xp, 8., Color::srgb(0.75, 0.2, 0.85));
+                spawn_text(row, HudText::Level);
+            });
+
+            hud.spawn(Node {
+                column_gap: Val::Px(16.),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_text(row, HudText::Timer);
+                spawn_text(row, HudText::Kills);
+            });
+        });
+}
+
+pub fn update_hud_bars(
+    player_query: Query<(&Player, &Health2d)>,
+    mut bar_query: Query<(&HudBar, &mut Node)>,
+) {
+    let Ok((player, health)) = player_query.get_single() else {
+        return;
+    };
+
+    for (bar, mut node) in bar_query.iter_mut() {
+        let fraction = match bar {
+            HudBar::Health => health.health / health.max_health,
+            HudBar::Shield => health
+                .shield
+                .as_ref()
+                .map_or(0., |shield| shield.amount / shield.max),
+            HudBar::Xp => player.level_progress(),
+        };
+
+        node.width = Val::Percent(fraction.clamp(0., 1.) * 100.);
+    }
+}
+
+pub fn update_hud_text(
+    state: Res<PhaseState>,
+    player_query: Query<(&Player, &Health2d)>,
+    mut text_query: Query<(&HudText, &mut Text)>,
+) {
+    let Ok((player, health)) = player_query.get_single() else {
+        return;
+    };
+
+    let elapsed = state.score() as u32;
+
+    for (hud_text, mut text) in text_query.iter_mut() {
+        text.0 = match hud_text {
+            HudText::Health => format!("{:.0}/{:.0}", health.health.max(0.), health.max_health),
+            HudText::Level => format!("Lv {}", player.level),
+            HudText::Timer => format!("{:02}:{:02}", elapsed / 60, elapsed % 60),
+            HudText::Kills => format!("Kills {}", state.kills),
+        };
+    }
+}
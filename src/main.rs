@@ -1,13 +1,23 @@
+mod boss;
+mod damage_number;
 mod debug;
 mod enemy;
 mod health;
+mod hud;
+mod particle;
 mod phase;
+mod pickup;
 mod player;
 mod shared;
 mod shot;
+mod spawn;
 mod systems;
 mod utils;
 mod timefade;
+mod tween;
+mod upgrade;
+mod wave;
+mod stats;
 mod quadtree;
 
 use crate::phase::PhasePlugin;
@@ -0,0 +1,308 @@
+use crate::timefade::{move_and_fade_particles, MoveAndFade};
+use bevy::app::{App, Plugin, PostUpdate, Update};
+use bevy::asset::Assets;
+use bevy::color::{Alpha, Color, Mix};
+use bevy::math::Vec2;
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+use std::ops::Range;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleMesh>()
+            .add_systems(
+                PostUpdate,
+                emit_particles.after(TransformSystem::TransformPropagate),
+            )
+            .add_systems(Update, particle_over_life.after(move_and_fade_particles));
+    }
+}
+
+/// Every particle is the same unit circle scaled by its size, so the mesh is shared.
+#[derive(Resource)]
+pub struct ParticleMesh(Handle<Mesh>);
+
+impl FromWorld for ParticleMesh {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+
+        Self(meshes.add(Circle::new(1.)))
+    }
+}
+
+#[derive(Clone)]
+pub enum EmitterMode {
+    /// Spawns a random amount of particles in `count` once.
+    Burst { count: Range<u32> },
+    /// Spawns `rate_ps` particles per second until `duration` finishes, forever when `None`.
+    Continuous {
+        rate_ps: f32,
+        duration: Option<Timer>,
+    },
+}
+
+/// Spawns [`MoveAndFade`] particles from the entity position.
+///
+/// The emitter entity is despawned once it has nothing left to emit, so attach it as a
+/// child when it should follow something else.
+#[derive(Component, Clone)]
+#[require(Transform)]
+pub struct ParticleEmitter {
+    pub mode: EmitterMode,
+
+    pub lifetime: Range<f32>,
+    pub speed: Range<f32>,
+    pub deceleration: Range<f32>,
+
+    pub direction: Vec2,
+    /// Spread around `direction` in radians, `TAU` emits in every direction.
+    pub cone_angle: f32,
+
+    pub start_color: Color,
+    pub end_color: Color,
+    pub start_size: f32,
+    pub end_size: f32,
+
+    pub gravity: Vec2,
+    /// Fraction of the velocity lost per second.
+    pub drag: f32,
+
+    accumulated: f32,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            mode: EmitterMode::Burst { count: 7..20 },
+            lifetime: 0.3..1.3,
+            speed: 350.0..450.0,
+            deceleration: 5.0..10.0,
+            direction: Vec2::X,
+            cone_angle: TAU,
+            start_color: Color::srgb(7.5, 7.5, 0.),
+            end_color: Color::srgb(7.5, 7.5, 0.),
+            start_size: 1.,
+            end_size: 1.,
+            gravity: Vec2::ZERO,
+            drag: 0.,
+            accumulated: 0.,
+        }
+    }
+}
+
+impl ParticleEmitter {
+    pub fn death_burst() -> Self {
+        Self::default()
+    }
+
+    pub fn explosion() -> Self {
+        Self {
+            mode: EmitterMode::Burst { count: 40..60 },
+            lifetime: 0.3..0.6,
+            speed: 300.0..600.0,
+            start_color: Color::srgb(8., 3., 0.5),
+            end_color: Color::srgb(3., 0.3, 0.),
+            start_size: 3.,
+            end_size: 0.5,
+            drag: 3.,
+            ..default()
+        }
+    }
+
+    pub fn muzzle_flash(direction: Vec2) -> Self {
+        Self {
+            mode: EmitterMode::Burst { count: 3..6 },
+            lifetime: 0.05..0.15,
+            speed: 150.0..250.0,
+            deceleration: 0.0..0.0,
+            direction,
+            cone_angle: 0.6,
+            start_color: Color::srgb(8., 6., 1.),
+            end_color: Color::srgb(6., 1.5, 0.),
+            start_size: 1.5,
+            end_size: 0.5,
+            ..default()
+        }
+    }
+
+    pub fn hit_sparks(direction: Vec2) -> Self {
+        Self {
+            mode: EmitterMode::Burst { count: 2..5 },
+            lifetime: 0.1..0.25,
+            speed: 100.0..200.0,
+            deceleration: 0.0..0.0,
+            direction,
+            cone_angle: 1.2,
+            start_color: Color::srgb(7.5, 7.5, 7.5),
+            end_color: Color::srgb(7.5, 2., 0.),
+            drag: 4.,
+            ..default()
+        }
+    }
+
+    pub fn level_up() -> Self {
+        Self {
+            mode: EmitterMode::Burst { count: 30..40 },
+            lifetime: 0.5..0.9,
+            speed: 150.0..250.0,
+            deceleration: 0.0..0.0,
+            start_color: Color::srgb(5.5, 0., 5.5),
+            end_color: Color::srgb(5., 5., 5.),
+            start_size: 2.,
+            end_size: 0.5,
+            drag: 2.,
+            ..default()
+        }
+    }
+
+    pub fn trail(color: Color) -> Self {
+        Self {
+            mode: EmitterMode::Continuous {
+                rate_ps: 20.,
+                duration: None,
+            },
+            lifetime: 0.2..0.4,
+            speed: 0.0..10.0,
+            deceleration: 0.0..0.0,
+            start_color: color,
+            end_color: color,
+            start_size: 2.,
+            end_size: 0.5,
+            ..default()
+        }
+    }
+
+    fn spawn_count(&mut self, delta_secs: f32) -> u32 {
+        match &mut self.mode {
+            EmitterMode::Burst { count } => sample_u32(count),
+            EmitterMode::Continuous { rate_ps, .. } => {
+                self.accumulated += *rate_ps * delta_secs;
+
+                let count = self.accumulated.floor();
+                self.accumulated -= count;
+
+                count as u32
+            }
+        }
+    }
+
+    fn finished(&self) -> bool {
+        match &self.mode {
+            EmitterMode::Burst { .. } => true,
+            EmitterMode::Continuous { duration, .. } => {
+                duration.as_ref().is_some_and(|timer| timer.finished())
+            }
+        }
+    }
+}
+
+/// Over-life parameters copied from the emitter, applied on top of [`MoveAndFade`].
+#[derive(Component)]
+pub struct Particle {
+    pub start_color: Color,
+    pub end_color: Color,
+    pub start_size: f32,
+    pub end_size: f32,
+    pub gravity: Vec2,
+    pub drag: f32,
+}
+
+fn sample(range: &Range<f32>) -> f32 {
+    if range.start >= range.end {
+        return range.start;
+    }
+
+    rand::thread_rng().gen_range(range.clone())
+}
+
+fn sample_u32(range: &Range<u32>) -> u32 {
+    if range.start >= range.end {
+        return range.start;
+    }
+
+    rand::thread_rng().gen_range(range.clone())
+}
+
+pub fn emit_particles(
+    time: Res<Time>,
+    mut commands: Commands,
+    mesh: Res<ParticleMesh>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut emitter_query: Query<(Entity, &mut ParticleEmitter, &GlobalTransform)>,
+) {
+    for (entity, mut emitter, global_transform) in emitter_query.iter_mut() {
+        if let EmitterMode::Continuous {
+            duration: Some(timer),
+            ..
+        } = &mut emitter.mode
+        {
+            timer.tick(time.delta());
+        }
+
+        let position = global_transform.translation();
+
+        for _ in 0..emitter.spawn_count(time.delta_secs()) {
+            let angle = (rand::random::<f32>() - 0.5) * emitter.cone_angle;
+            let direction = Vec2::from_angle(angle).rotate(emitter.direction.normalize_or_zero());
+
+            commands.spawn((
+                MoveAndFade {
+                    speed: sample(&emitter.speed),
+                    direction,
+                    deceleration: sample(&emitter.deceleration),
+                    timer: Timer::from_seconds(sample(&emitter.lifetime), TimerMode::Once),
+                },
+                Particle {
+                    start_color: emitter.start_color,
+                    end_color: emitter.end_color,
+                    start_size: emitter.start_size,
+                    end_size: emitter.end_size,
+                    gravity: emitter.gravity,
+                    drag: emitter.drag,
+                },
+                Transform::from_translation(position).with_scale(Vec3::splat(emitter.start_size)),
+                Mesh2d(mesh.0.clone()),
+                MeshMaterial2d(materials.add(emitter.start_color)),
+            ));
+        }
+
+        if emitter.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn particle_over_life(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut particle_query: Query<(
+        &Particle,
+        &mut MoveAndFade,
+        &mut Transform,
+        &MeshMaterial2d<ColorMaterial>,
+    )>,
+) {
+    for (particle, mut move_and_fade, mut transform, material) in particle_query.iter_mut() {
+        let life = move_and_fade.timer.fraction();
+
+        let velocity =
+            move_and_fade.direction * move_and_fade.speed + particle.gravity * time.delta_secs();
+        let velocity = velocity * (1. - particle.drag * time.delta_secs()).max(0.);
+
+        move_and_fade.direction = velocity.normalize_or_zero();
+        move_and_fade.speed = velocity.length();
+
+        let size = particle.start_size + (particle.end_size - particle.start_size) * life;
+        transform.scale = Vec3::new(size, size, transform.scale.z);
+
+        if let Some(color_material) = materials.get_mut(material.id()) {
+            let fade = move_and_fade.timer.fraction_remaining();
+            let color = particle.start_color.mix(&particle.end_color, life);
+
+            color_material.color = color.with_alpha(color.alpha() * fade);
+        }
+    }
+}
@@ -1,15 +1,24 @@
+use crate::boss::BossPlugin;
+use crate::damage_number::DamageNumberPlugin;
 use crate::debug::ShowInfoPlugin;
 use crate::enemy::{Enemy, EnemyPlugin};
-use crate::health::HealthPlugin;
+use crate::health::{DamageType, HealthPlugin};
+use crate::hud::HudPlugin;
+use crate::particle::ParticlePlugin;
 use crate::phase::systems::*;
 use crate::phase::PhaseStatus::Running;
+use crate::pickup::PickupPlugin;
 use crate::player::PlayerPlugin;
 use crate::quadtree::{Bounds, QuadTree};
 use crate::shot::ShotPlugin;
-use bevy::app::{App, Last, Plugin, Startup, Update};
-use bevy::color::LinearRgba;
+use crate::spawn::{SpawnPlugin, SpawnStrategy};
+use crate::stats::StatsPlugin;
+use crate::tween::TweenPlugin;
+use crate::upgrade::UpgradePlugin;
+use crate::wave::WavePlugin;
+use bevy::app::{App, Plugin, Startup, Update};
 use bevy::prelude::*;
-use bevy::utils::Instant;
+use std::collections::HashMap;
 
 mod systems;
 
@@ -22,58 +31,69 @@ pub enum PhaseStatus {
 
 #[derive(Resource)]
 pub struct PhaseState {
-    pub last_enemy_spawn_in_seconds: f32,
-    pub start_at: Instant,
+    /// Seconds of gameplay, does not advance while the phase is paused.
+    pub elapsed: f32,
 
-    pub base_spawn_time: f32,
-    pub enemy_spawn_time: f32,
     pub status: PhaseStatus,
+
+    /// Enemies killed by the player.
+    pub kills: u32,
+    /// Player kills by the damage type of the killing blow.
+    pub kills_by_type: HashMap<DamageType, u32>,
+    /// Enemies cleared by pickups like the bomb, counted in `kills` but not by type.
+    pub pickup_kills: u32,
+    /// Damage the player dealt, after mitigation.
+    pub damage_dealt: f32,
 }
 
 impl Default for PhaseState {
     fn default() -> Self {
         Self {
-            start_at: Instant::now(),
-            base_spawn_time: 1.,
-            enemy_spawn_time: 1.,
-            last_enemy_spawn_in_seconds: 0.,
+            elapsed: 0.,
             status: Running,
+            kills: 0,
+            kills_by_type: HashMap::new(),
+            pickup_kills: 0,
+            damage_dealt: 0.,
         }
     }
 }
 
 impl PhaseState {
     pub fn score(&self) -> f32 {
-        self.start_at.elapsed().as_secs_f32()
+        self.elapsed
     }
 }
 
-#[derive(PartialEq, Clone)]
-pub enum EnemySpeed {
-    RandomSlow,
-    RandomNormal,
-    RandomFast,
-    Value(f32),
+pub fn phase_running(state: Res<PhaseState>) -> bool {
+    state.status == PhaseStatus::Running
 }
 
 #[derive(Event, Clone)]
 pub struct SpawnEnemyEvent {
-    pub size: f32,
-    pub speed: EnemySpeed,
-
-    pub color: Color,
+    /// Id of the archetype in the enemy archetypes file.
+    pub archetype: String,
+    /// Picked by the spawn strategy, behind a warning marker, when `None`.
+    pub position: Option<Vec3>,
+    pub impulse: Vec2,
+    /// Overrides the default spawn strategy when no position is given.
+    pub strategy: Option<SpawnStrategy>,
+}
 
-    pub xp_on_death: u32,
+impl SpawnEnemyEvent {
+    pub fn new(archetype: &str) -> Self {
+        Self {
+            archetype: archetype.to_string(),
+            position: None,
+            impulse: Vec2::ZERO,
+            strategy: None,
+        }
+    }
 }
 
 impl Default for SpawnEnemyEvent {
     fn default() -> Self {
-        Self {
-            size: 10.,
-            speed: EnemySpeed::RandomNormal,
-            color: Color::LinearRgba(LinearRgba::RED),
-            xp_on_death: 1,
-        }
+        Self::new("chaser")
     }
 }
 
@@ -94,10 +114,22 @@ impl Plugin for PhasePlugin {
             .add_plugins(EnemyPlugin)
             .add_plugins(HealthPlugin)
             .add_plugins(ShotPlugin)
+            .add_plugins(ParticlePlugin)
+            .add_plugins(TweenPlugin)
+            .add_plugins(DamageNumberPlugin)
+            .add_plugins(HudPlugin)
+            .add_plugins(UpgradePlugin)
+            .add_plugins(StatsPlugin)
+            .add_plugins(PickupPlugin)
+            .add_plugins(BossPlugin)
+            .add_plugins(SpawnPlugin)
+            .add_plugins(WavePlugin)
             .add_systems(Main, insert_resources)
             .add_systems(Startup, setup)
             .add_systems(Update, track_palyer_where_to_shoot)
             .add_systems(Update, spawn_enemy_listener)
-            .add_systems(Last, increase_spawn_rate_over_time);
+            .add_systems(Update, tick_phase_time)
+            .add_systems(Update, count_damage_dealt)
+            .add_systems(Update, count_kills);
     }
 }
@@ -1,31 +1,52 @@
-use crate::enemy::Enemy;
-use crate::health::Health2d;
-use crate::phase::{EnemySpeed, PhaseState, SpawnEnemyEvent};
+use crate::boss::Boss;
+use crate::enemy::archetype::CurrentEnemyArchetypes;
+use crate::enemy::elite::{Elite, ELITE_CHANCE};
+use crate::enemy::{Enemy, Impulse};
+use crate::health::{
+    CriticalStrike, DamageCause, DamageDealtEvent, DamageType, DeathEvent, Health2d,
+};
+use crate::particle::ParticleEmitter;
+use crate::phase::{PhaseState, SpawnEnemyEvent};
+use crate::pickup::drops::Drops;
 use crate::player::player::Player;
 use crate::quadtree::{Bounds, QuadTree};
 use crate::shared::InfoText;
 use crate::shot::Shooter;
+use crate::stats::Stat;
+use crate::tween::{Ease, Lens, OnComplete, Tween, TweenStep};
 use crate::utils::random_direction;
+use crate::wave::difficulty::DifficultyScale;
+use crate::wave::RECYCLE_DISTANCE;
 use bevy::asset::Assets;
 use bevy::color::palettes::css::RED;
 use bevy::color::palettes::tailwind::FUCHSIA_500;
-use bevy::color::{Color, LinearRgba, Luminance};
+use bevy::color::{Color, LinearRgba};
+use bevy::log::warn;
 use bevy::math::{Vec2, Vec3};
 use bevy::prelude::{
-    AmbientLight, Circle, ColorMaterial, Commands, Entity, EventReader, EventWriter, Mesh, Mesh2d,
-    MeshMaterial2d, Query, ResMut, Text, Transform, Window, With,
+    AmbientLight, BuildChildren, Circle, ColorMaterial, Commands, Entity, EventReader, Local, Mesh,
+    Mesh2d, MeshMaterial2d, Query, Res, ResMut, Text, Time, Transform, Window, With,
 };
 use bevy::text::{TextColor, TextFont};
+use bevy::utils::default;
 use bevy::window::PrimaryWindow;
 use rand::random;
-use std::iter;
 
 pub fn insert_resources(mut commands: Commands, window_query: Query<&Window, With<PrimaryWindow>>) {
     let window = window_query.get_single().unwrap();
 
+    // Enemies spawn off screen and are only recycled once this far from the player, the tree
+    // covers all of it so they are indexed before they walk into view
+    let reach = Vec2::new(window.width(), window.height()).length() * RECYCLE_DISTANCE;
+
     let tree = QuadTree::<Entity, Enemy>::new(
-        Bounds::new_simple(window.width(), window.height()),
-        Some(4),
+        Bounds::new(
+            -reach,
+            -reach,
+            window.width() + reach,
+            window.height() + reach,
+        ),
+        Some(5),
     );
 
     commands.insert_resource(tree);
@@ -36,14 +57,10 @@ pub fn setup(
     window_query: Query<&Window, With<PrimaryWindow>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut spawn_enemy_event_writter: EventWriter<SpawnEnemyEvent>,
 ) {
     let window = window_query.get_single().unwrap();
 
-    commands.insert_resource(PhaseState {
-        enemy_spawn_time: 6.,
-        ..PhaseState::default()
-    });
+    commands.insert_resource(PhaseState::default());
     commands.insert_resource(AmbientLight {
         color: RED.into(),
         brightness: 50.,
@@ -62,90 +79,134 @@ pub fn setup(
 
     let transform_center = Transform::from_xyz(window.width() / 2., window.height() / 2., 1.);
 
+    let stats = Player::base_stats();
+
     commands.spawn((
-        Player {
-            speed: 500.,
-            ..Player::default()
+        Health2d {
+            xp_on_death: 0,
+            ..Health2d::full_health(stats.get(Stat::MaxHealth))
         },
         Shooter {
-            fire_rate: 5.,
-            damage: 1.,
+            fire_rate: stats.get(Stat::FireRate),
+            damage: stats.get(Stat::Damage),
+            damage_type: DamageType::Kinetic,
+            on_hit: Vec::new(),
             direction: Vec2::new(0., 1.),
+            projectiles: stats.get(Stat::Projectiles) as u32,
             last_shoot: std::time::Instant::now(),
             should_shoot: true,
         },
+        CriticalStrike {
+            chance: stats.get(Stat::CritChance),
+            multiplier: stats.get(Stat::CritMultiplier),
+        },
         Transform::from(transform_center),
-        Mesh2d(meshes.add(Circle::new(Player::default().size))),
+        Mesh2d(meshes.add(Circle::new(stats.base(Stat::Size)))),
         MeshMaterial2d(materials.add(ColorMaterial::from(Color::Srgba(FUCHSIA_500)))),
+        stats,
+        Player::default(),
     ));
-
-    let events = iter::repeat(SpawnEnemyEvent::default()).take(5);
-
-    spawn_enemy_event_writter.send_batch(events);
 }
 
 pub fn spawn_enemy_listener(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
     mut event_reader: EventReader<SpawnEnemyEvent>,
-    player_query: Query<(&Player, &Transform), With<Player>>,
-    mut state: ResMut<PhaseState>,
+    player_query: Query<&Transform, With<Player>>,
+    archetypes: CurrentEnemyArchetypes,
+    difficulty: Res<DifficultyScale>,
+    mut pending: Local<Vec<(Vec3, SpawnEnemyEvent)>>,
 ) {
-    let window = window_query.get_single().unwrap();
+    // Spawns requested before the archetypes are loaded wait for them, the ones without a
+    // position go through the spawn warning first
+    pending.extend(
+        event_reader
+            .read()
+            .filter_map(|event| Some((event.position?, event.clone()))),
+    );
 
-    for event in event_reader.read() {
-        if let Ok((player, transform)) = player_query.get_single() {
-            use rand::Rng;
-
-            let enemy_position = loop {
-                let random_x = random::<f32>() * window.width();
-                let random_y = random::<f32>() * window.height();
-
-                let enemy_position = Vec3::new(random_x, random_y, 0.);
-
-                if enemy_position.distance(transform.translation) > player.size * 10. {
-                    break enemy_position;
-                }
-            };
-            let direction = (transform.translation - enemy_position).normalize();
-
-            let speed = match event.speed {
-                EnemySpeed::RandomSlow => rand::thread_rng().gen_range(100..200) as f32,
-                EnemySpeed::RandomNormal => rand::thread_rng().gen_range(200..300) as f32,
-                EnemySpeed::RandomFast => rand::thread_rng().gen_range(300..500) as f32,
-                EnemySpeed::Value(x) => x,
-            };
-
-            let color = match event.speed {
-                EnemySpeed::RandomSlow => event.color.darker(0.2),
-                EnemySpeed::RandomFast => event.color.lighter(0.2),
-                _ => event.color,
-            };
-
-            // Add Heath bar with
-            // https://bevy-cheatbook.github.io/fundamentals/hierarchy.html
-            let ent_command = commands.spawn((
-                Enemy {
-                    size: event.size,
-                    speed,
-                    direction: direction.truncate(),
-                },
-                Health2d::full_health(1.),
-                Transform::from_translation(enemy_position),
-                Mesh2d(meshes.add(Circle::new(event.size))),
-                MeshMaterial2d(materials.add(ColorMaterial::from(color))),
-            ));
-
-            // quad_tree.insert(
-            //     ent_command.id(),
-            //     ent_command.id(),
-            //     Vec2::new(enemy_position.x, enemy_position.y),
-            // );
-
-            state.last_enemy_spawn_in_seconds = 0.;
+    if !archetypes.loaded() {
+        if archetypes.failed() && !pending.is_empty() {
+            warn!(
+                "Enemy archetypes failed to load, dropping {} spawns",
+                pending.len()
+            );
+            pending.clear();
+        }
+
+        return;
+    }
+
+    // Spawns wait for a player to head to
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    for (enemy_position, event) in pending.drain(..) {
+        let Some((archetype, archetype_mesh)) = archetypes.get(&event.archetype) else {
+            warn!("Unknown enemy archetype {}", event.archetype);
+            continue;
+        };
+
+        let direction = (player_transform.translation - enemy_position).normalize_or_zero();
+
+        let speed = archetype.roll_speed(&mut rand::thread_rng()) * difficulty.speed;
+
+        let mut ent_command = commands.spawn((
+            Enemy {
+                size: archetype.size,
+                speed,
+                direction: direction.truncate(),
+                contact_damage: archetype.contact_damage,
+            },
+            Health2d {
+                xp_on_death: archetype.xp,
+                ..Health2d::full_health(archetype.health * difficulty.health)
+            },
+            archetype.behaviour.clone(),
+            archetype.resistances.clone(),
+            archetype.steering.clone(),
+            Transform::from_translation(enemy_position).with_scale(Vec3::ZERO),
+            Tween::new(TweenStep {
+                lenses: vec![Lens::Scale {
+                    from: Vec3::ZERO,
+                    to: Vec3::ONE,
+                }],
+                duration: 0.3,
+                ease: Ease::BackOut,
+                ..default()
+            })
+            .with_on_complete(OnComplete::Remove),
+            Mesh2d(archetype_mesh.mesh.clone()),
+            MeshMaterial2d(archetype_mesh.material.clone()),
+        ));
+
+        if let Some(drops) = &archetype.drops {
+            ent_command.insert(Drops(drops.clone()));
+        }
+
+        if let Some(split) = &archetype.split {
+            ent_command.insert(split.clone());
+        }
+
+        if event.impulse != Vec2::ZERO {
+            ent_command.insert(Impulse(event.impulse));
+        }
+
+        if let Some(boss) = &archetype.boss {
+            ent_command.insert(Boss::new(boss.clone()));
+        } else if random::<f32>() < ELITE_CHANCE {
+            ent_command.insert(Elite::roll(&mut rand::thread_rng()));
+        }
+
+        if archetype.trail {
+            ent_command.with_child(ParticleEmitter::trail(archetype.color()));
         }
+
+        // quad_tree.insert(
+        //     ent_command.id(),
+        //     ent_command.id(),
+        //     Vec2::new(enemy_position.x, enemy_position.y),
+        // );
     }
 }
 
@@ -177,6 +238,41 @@ pub fn track_palyer_where_to_shoot(
     }
 }
 
-pub fn increase_spawn_rate_over_time(mut state: ResMut<PhaseState>) {
-    state.enemy_spawn_time = state.base_spawn_time - (state.start_at.elapsed().as_secs_f32() / 60.);
+pub fn tick_phase_time(time: Res<Time>, mut state: ResMut<PhaseState>) {
+    state.elapsed += time.delta_secs();
+}
+
+pub fn count_damage_dealt(
+    mut event_reader: EventReader<DamageDealtEvent>,
+    player_query: Query<(), With<Player>>,
+    mut state: ResMut<PhaseState>,
+) {
+    for event in event_reader.read() {
+        if event
+            .source
+            .is_some_and(|source| player_query.contains(source))
+        {
+            state.damage_dealt += event.damage;
+        }
+    }
+}
+
+pub fn count_kills(
+    mut event_reader: EventReader<DeathEvent>,
+    player_query: Query<(), With<Player>>,
+    mut state: ResMut<PhaseState>,
+) {
+    for event in event_reader.read() {
+        match (event.cause, event.killer) {
+            (DamageCause::Pickup, _) => {
+                state.kills += 1;
+                state.pickup_kills += 1;
+            }
+            (DamageCause::Attack, Some(killer)) if player_query.contains(killer) => {
+                state.kills += 1;
+                *state.kills_by_type.entry(event.damage_type).or_default() += 1;
+            }
+            (DamageCause::Attack, _) => {}
+        }
+    }
 }
@@ -0,0 +1,114 @@
+use crate::pickup::PickupKind;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Drop tables for every enemy type, loaded from a `.drops.ron` file.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct DropTables {
+    pub tables: HashMap<String, DropTable>,
+}
+
+#[derive(Resource)]
+pub struct DropTablesHandle(pub Handle<DropTables>);
+
+/// The loaded drop tables, `None` until the asset server finished loading them.
+#[derive(SystemParam)]
+pub struct CurrentDropTables<'w> {
+    handle: Res<'w, DropTablesHandle>,
+    tables: Res<'w, Assets<DropTables>>,
+}
+
+impl CurrentDropTables<'_> {
+    pub fn get(&self, name: &str) -> Option<&DropTable> {
+        self.tables
+            .get(&self.handle.0)
+            .and_then(|tables| tables.tables.get(name))
+    }
+}
+
+/// Name of the drop table rolled when the entity dies.
+#[derive(Component)]
+pub struct Drops(pub String);
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DropEntry {
+    /// Between 0 and 1, rolled independently for each entry.
+    pub chance: f32,
+    pub item: PickupKind,
+    #[serde(default = "one")]
+    pub min: u32,
+    #[serde(default = "one")]
+    pub max: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Deserialize, Clone, Default, Debug)]
+pub struct DropTable {
+    pub entries: Vec<DropEntry>,
+}
+
+impl DropTable {
+    pub fn roll(&self, rng: &mut impl Rng) -> Vec<PickupKind> {
+        let mut items = Vec::new();
+
+        for entry in self.entries.iter() {
+            if rng.gen::<f32>() >= entry.chance {
+                continue;
+            }
+
+            let quantity = rng.gen_range(entry.min..=entry.max.max(entry.min));
+
+            items.extend((0..quantity).map(|_| entry.item));
+        }
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roll_respects_chance_and_quantity() {
+        let table = DropTable {
+            entries: vec![
+                DropEntry {
+                    chance: 1.,
+                    item: PickupKind::Bomb,
+                    min: 2,
+                    max: 2,
+                },
+                DropEntry {
+                    chance: 0.,
+                    item: PickupKind::Vacuum,
+                    min: 1,
+                    max: 1,
+                },
+            ],
+        };
+
+        let items = table.roll(&mut rand::thread_rng());
+
+        assert_eq!(items, vec![PickupKind::Bomb, PickupKind::Bomb]);
+    }
+
+    #[test]
+    fn bundled_drop_tables_parse() {
+        let tables: DropTables =
+            bevy::asset::ron::de::from_str(include_str!("../../assets/enemies.drops.ron")).unwrap();
+
+        for table in tables.tables.values() {
+            for entry in table.entries.iter() {
+                assert!((0. ..=1.).contains(&entry.chance));
+                assert!(entry.min <= entry.max);
+            }
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use crate::boss::Boss;
+use crate::enemy::Enemy;
+use crate::health::{DamageCause, DamageEvent, DamageType, Health2d};
+use crate::player::player::Player;
+use crate::player::PlayerReceiveXpEvent;
+use crate::stats::{Stat, StatModifier, Stats};
+use crate::utils::ron_asset::RonAssetLoader;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::Assets;
+use bevy::color::Color;
+use bevy::math::Vec3;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use drops::{DropTables, DropTablesHandle};
+use serde::Deserialize;
+use std::time::Duration;
+
+pub mod drops;
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnPickupEvent>()
+            .add_event::<PickupCollectedEvent>()
+            .init_asset::<DropTables>()
+            .register_asset_loader(RonAssetLoader::<DropTables>::new(&["drops.ron"]))
+            .init_resource::<PickupAssets>()
+            .add_systems(Startup, load_drop_tables)
+            .add_systems(Update, spawn_pickup_listener)
+            .add_systems(Update, magnet_pickups)
+            .add_systems(Update, collect_pickups.after(magnet_pickups))
+            .add_systems(Update, pickup_collected_listener.after(collect_pickups))
+            .add_systems(Update, merge_xp_gems.after(spawn_pickup_listener));
+    }
+}
+
+/// Above this many gems on screen the ones farthest from the player are merged together.
+const MAX_XP_GEMS: usize = 150;
+
+/// Speed a magnetized pickup starts at, it accelerates until it reaches the player.
+const MAGNET_SPEED: f32 = 150.;
+const MAGNET_ACCELERATION: f32 = 1200.;
+
+/// Source of the stat modifiers added by buff pickups.
+const BUFF_SOURCE: &str = "buff_pickup";
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum PickupKind {
+    Xp(u32),
+    /// Heals the player by the amount.
+    Health(f32),
+    /// Kills every enemy on screen, except bosses.
+    Bomb,
+    /// Pulls every XP gem to the player.
+    Vacuum,
+    /// Adds a stat modifier to the player for a few seconds.
+    Buff {
+        modifier: StatModifier,
+        seconds: f32,
+    },
+}
+
+impl PickupKind {
+    fn radius(self) -> f32 {
+        match self {
+            PickupKind::Xp(xp) => 4. + (xp as f32).sqrt() * 2.,
+            _ => 8.,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct SpawnPickupEvent {
+    pub kind: PickupKind,
+    pub position: Vec3,
+}
+
+#[derive(Event)]
+pub struct PickupCollectedEvent {
+    pub kind: PickupKind,
+}
+
+/// Something lying on the ground that the player collects by touching it.
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    /// Set once the pickup enters the magnet radius, it then follows the player until collected.
+    magnet_speed: Option<f32>,
+}
+
+/// Pickups are unit circles scaled by their radius, so the mesh is shared.
+#[derive(Resource)]
+pub struct PickupAssets {
+    mesh: Handle<Mesh>,
+    xp_material: Handle<ColorMaterial>,
+    health_material: Handle<ColorMaterial>,
+    bomb_material: Handle<ColorMaterial>,
+    vacuum_material: Handle<ColorMaterial>,
+    buff_material: Handle<ColorMaterial>,
+}
+
+impl PickupAssets {
+    fn material(&self, kind: PickupKind) -> Handle<ColorMaterial> {
+        match kind {
+            PickupKind::Xp(_) => self.xp_material.clone(),
+            PickupKind::Health(_) => self.health_material.clone(),
+            PickupKind::Bomb => self.bomb_material.clone(),
+            PickupKind::Vacuum => self.vacuum_material.clone(),
+            PickupKind::Buff { .. } => self.buff_material.clone(),
+        }
+    }
+}
+
+impl FromWorld for PickupAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Circle::new(1.));
+
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let mut material = |color: Color| materials.add(ColorMaterial::from(color));
+
+        Self {
+            mesh,
+            xp_material: material(Color::srgb(0.3, 2.5, 3.)),
+            health_material: material(Color::srgb(0.2, 3., 0.4)),
+            bomb_material: material(Color::srgb(3., 0.8, 0.1)),
+            vacuum_material: material(Color::srgb(2.5, 2.5, 3.)),
+            buff_material: material(Color::srgb(3., 2.5, 0.3)),
+        }
+    }
+}
+
+pub fn load_drop_tables(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(DropTablesHandle(asset_server.load("enemies.drops.ron")));
+}
+
+pub fn spawn_pickup_listener(
+    mut commands: Commands,
+    mut event_reader: EventReader<SpawnPickupEvent>,
+    assets: Res<PickupAssets>,
+) {
+    for event in event_reader.read() {
+        commands.spawn((
+            Pickup {
+                kind: event.kind,
+                magnet_speed: None,
+            },
+            Transform::from_translation(event.position.truncate().extend(0.5))
+                .with_scale(Vec3::splat(event.kind.radius())),
+            Mesh2d(assets.mesh.clone()),
+            MeshMaterial2d(assets.material(event.kind)),
+        ));
+    }
+}
+
+pub fn magnet_pickups(
+    time: Res<Time>,
+    player_query: Query<(&Stats, &Transform), With<Player>>,
+    mut pickup_query: Query<(&mut Pickup, &mut Transform), Without<Player>>,
+) {
+    let Ok((stats, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    let magnet_radius = stats.get(Stat::MagnetRadius);
+    let target = player_transform.translation.truncate();
+
+    for (mut pickup, mut transform) in pickup_query.iter_mut() {
+        let position = transform.translation.truncate();
+
+        let speed = match pickup.magnet_speed {
+            Some(speed) => speed + MAGNET_ACCELERATION * time.delta_secs(),
+            None if position.distance(target) < magnet_radius => MAGNET_SPEED,
+            None => continue,
+        };
+
+        pickup.magnet_speed = Some(speed);
+
+        let step = (target - position).clamp_length_max(speed * time.delta_secs());
+        transform.translation += step.extend(0.);
+    }
+}
+
+pub fn collect_pickups(
+    mut commands: Commands,
+    player_query: Query<(&Stats, &Transform), With<Player>>,
+    pickup_query: Query<(Entity, &Pickup, &Transform), Without<Player>>,
+    mut event_writer: EventWriter<PickupCollectedEvent>,
+) {
+    let Ok((stats, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    let size = stats.get(Stat::Size);
+
+    for (entity, pickup, transform) in pickup_query.iter() {
+        let distance = transform
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate());
+
+        if distance < size + pickup.kind.radius() {
+            commands.entity(entity).despawn();
+
+            event_writer.send(PickupCollectedEvent { kind: pickup.kind });
+        }
+    }
+}
+
+/// Enemy health, kept apart from the player health healed by the same system. Bosses are
+/// left out so a bomb cannot one-shot them.
+type EnemyHealthQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Health2d, &'static Transform),
+    (With<Enemy>, Without<Player>, Without<Boss>),
+>;
+
+pub fn pickup_collected_listener(
+    mut event_reader: EventReader<PickupCollectedEvent>,
+    mut xp_writer: EventWriter<PlayerReceiveXpEvent>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut player_query: Query<(&mut Stats, &mut Health2d), With<Player>>,
+    enemy_query: EnemyHealthQuery,
+    mut pickup_query: Query<&mut Pickup>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    for event in event_reader.read() {
+        match event.kind {
+            PickupKind::Xp(xp) => {
+                xp_writer.send(PlayerReceiveXpEvent { xp });
+            }
+            PickupKind::Health(amount) => {
+                if let Ok((_, mut health)) = player_query.get_single_mut() {
+                    health.health = (health.health + amount).min(health.max_health);
+                }
+            }
+            PickupKind::Bomb => {
+                let Ok(window) = window_query.get_single() else {
+                    continue;
+                };
+
+                let view = Rect::new(0., 0., window.width(), window.height());
+
+                for (entity, health, transform) in enemy_query.iter() {
+                    if !view.contains(transform.translation.truncate()) {
+                        continue;
+                    }
+
+                    damage_writer.send(DamageEvent {
+                        entity,
+                        damage: health.health,
+                        damage_type: DamageType::Fire,
+                        source: None,
+                        // Its kills are counted apart from the weapon kills
+                        cause: DamageCause::Pickup,
+                        unmitigated: true,
+                        knockback: None,
+                    });
+                }
+            }
+            PickupKind::Vacuum => {
+                for mut pickup in pickup_query.iter_mut() {
+                    if matches!(pickup.kind, PickupKind::Xp(_)) && pickup.magnet_speed.is_none() {
+                        pickup.magnet_speed = Some(MAGNET_SPEED);
+                    }
+                }
+            }
+            PickupKind::Buff { modifier, seconds } => {
+                if let Ok((mut stats, _)) = player_query.get_single_mut() {
+                    stats.add_timed_modifier(
+                        modifier,
+                        BUFF_SOURCE,
+                        Duration::from_secs_f32(seconds),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// How many of the farthest gems must be folded into one to get back to `max` gems.
+fn gems_to_merge(gems: usize, max: usize) -> usize {
+    if gems > max {
+        gems - max + 1
+    } else {
+        0
+    }
+}
+
+pub fn merge_xp_gems(
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    mut pickup_query: Query<(Entity, &mut Pickup, &mut Transform), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    // Gems already flying to the player are left alone
+    let mut gems: Vec<(Entity, u32, f32)> = pickup_query
+        .iter()
+        .filter_map(|(entity, pickup, transform)| match pickup.kind {
+            PickupKind::Xp(xp) if pickup.magnet_speed.is_none() => Some((
+                entity,
+                xp,
+                transform.translation.distance(player_transform.translation),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    let merge = gems_to_merge(gems.len(), MAX_XP_GEMS);
+
+    if merge == 0 {
+        return;
+    }
+
+    gems.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let total_xp = gems[..merge].iter().map(|(_, xp, _)| xp).sum();
+
+    for (entity, _, _) in gems[1..merge].iter() {
+        commands.entity(*entity).despawn();
+    }
+
+    if let Ok((_, mut pickup, mut transform)) = pickup_query.get_mut(gems[0].0) {
+        pickup.kind = PickupKind::Xp(total_xp);
+        transform.scale = Vec3::splat(pickup.kind.radius());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_down_to_the_limit() {
+        assert_eq!(gems_to_merge(10, 150), 0);
+        assert_eq!(gems_to_merge(150, 150), 0);
+        // Folding two gems into one removes a single gem
+        assert_eq!(gems_to_merge(151, 150), 2);
+        assert_eq!(gems_to_merge(160, 150), 11);
+    }
+
+    #[test]
+    fn bigger_gems_are_bigger() {
+        assert!(PickupKind::Xp(10).radius() > PickupKind::Xp(1).radius());
+    }
+}
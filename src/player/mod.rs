@@ -14,12 +14,18 @@ pub struct PlayerReceiveXpEvent {
     pub xp: u32,
 }
 
+/// Sent once per level gained, the new level is on [`player::Player`].
+#[derive(Event)]
+pub struct PlayerLevelUpEvent;
+
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<PlayerMovedEvent>()
             .add_event::<PlayerReceiveXpEvent>()
+            .add_event::<PlayerLevelUpEvent>()
             .add_systems(Update, player_movement)
             .add_systems(Update, receive_xp_listener)
-            .add_systems(Update, enemy_hit_player);
+            .add_systems(Update, enemy_hit_player)
+            .add_systems(Update, player_death_check);
     }
 }
@@ -1,35 +1,83 @@
+use crate::stats::{Stat, Stats};
 use bevy::prelude::Component;
 
 #[derive(Component)]
 pub struct Player {
-    pub max_health: u32,
-
-    pub size: f32,
-
-    pub fire_rate_ps: f32,
-    pub damage_shot: u32,
-    pub speed: f32,
-
     pub xp: u32,
+    pub level: u32,
 }
 
 impl Default for Player {
     fn default() -> Self {
-        Self {
-            max_health: 100,
-            fire_rate_ps: 1.0,
-            damage_shot: 10,
-            speed: 500.,
-            size: 20.,
-            xp: 0,
+        Self { xp: 0, level: 1 }
+    }
+}
+
+impl Player {
+    /// Stats the player starts every run with, upgrades and buffs modify them.
+    pub fn base_stats() -> Stats {
+        Stats::new([
+            (Stat::MaxHealth, 100.),
+            (Stat::Size, 20.),
+            (Stat::FireRate, 5.),
+            (Stat::Damage, 1.),
+            (Stat::Projectiles, 1.),
+            (Stat::Speed, 500.),
+            (Stat::MagnetRadius, 100.),
+            (Stat::CritChance, 0.05),
+            (Stat::CritMultiplier, 2.),
+        ])
+    }
+
+    /// Total XP needed to reach `level`, the player starts at level 1.
+    pub fn xp_for_level(level: u32) -> u32 {
+        let level = level.saturating_sub(1);
+
+        5 * level * (level + 1) / 2
+    }
+
+    pub fn level_for_xp(xp: u32) -> u32 {
+        let mut level = 1;
+
+        while Self::xp_for_level(level + 1) <= xp {
+            level += 1;
         }
+
+        level
+    }
+
+    /// Fraction of the way from the current level to the next one.
+    pub fn level_progress(&self) -> f32 {
+        let from = Self::xp_for_level(self.level);
+        let to = Self::xp_for_level(self.level + 1);
+
+        self.xp.saturating_sub(from) as f32 / (to - from) as f32
     }
 }
 
-pub type ApplyOnPlayer = fn(&mut Player) -> ();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl Player {
-    pub fn apply(&mut self, apply: ApplyOnPlayer) {
-        apply(self);
+    #[test]
+    fn level_follows_xp_curve() {
+        assert_eq!(Player::level_for_xp(0), 1);
+        assert_eq!(Player::level_for_xp(Player::xp_for_level(2) - 1), 1);
+        assert_eq!(Player::level_for_xp(Player::xp_for_level(2)), 2);
+        assert_eq!(Player::level_for_xp(Player::xp_for_level(5) + 1), 5);
+    }
+
+    #[test]
+    fn level_progress_is_relative_to_current_level() {
+        let mut player = Player {
+            level: 3,
+            ..Player::default()
+        };
+
+        player.xp = Player::xp_for_level(3);
+        assert_eq!(player.level_progress(), 0.);
+
+        player.xp = Player::xp_for_level(3) + 3;
+        assert_eq!(player.level_progress(), 0.2);
     }
 }
@@ -1,45 +1,97 @@
 use crate::enemy::Enemy;
+use crate::health::{DamageCause, DamageEvent, DamageType, Health2d};
 use crate::phase::{PhaseState, PhaseStatus};
 use crate::player::player::Player;
-use crate::player::{PlayerMovedEvent, PlayerReceiveXpEvent};
-use crate::timefade::MoveAndFade;
+use crate::player::{PlayerLevelUpEvent, PlayerMovedEvent, PlayerReceiveXpEvent};
+use crate::stats::{Stat, Stats};
+use crate::tween::{Ease, Lens, OnComplete, Tween, TweenStep};
+use crate::GameOver;
 use bevy::input::ButtonInput;
 use bevy::math::Vec3;
 use bevy::prelude::*;
 use bevy::time::Time;
 use bevy::window::PrimaryWindow;
 
+/// Seconds the player is safe from contact damage after being hit.
+const CONTACT_DAMAGE_COOLDOWN: f32 = 0.5;
+
 pub fn enemy_hit_player(
-    mut player_query: Query<(&Player, &Transform)>,
-    enemy_query: Query<(&Enemy, &Transform)>,
-    state: ResMut<PhaseState>,
+    time: Res<Time>,
+    player_query: Query<(Entity, &Stats, &Transform), With<Player>>,
+    enemy_query: Query<(Entity, &Enemy, &Transform)>,
+    state: Res<PhaseState>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut since_last_hit: Local<f32>,
 ) {
     if state.status != PhaseStatus::Running {
         return;
     }
 
-    if let Ok((player, player_transform)) = player_query.get_single_mut() {
-        for (enemy, enemy_transform) in enemy_query.iter() {
-            if player_transform
-                .translation
-                .distance(enemy_transform.translation)
-                < enemy.size + player.size
-            {
-                // TODO: Adicionar dano
-            }
+    *since_last_hit += time.delta_secs();
+
+    if *since_last_hit < CONTACT_DAMAGE_COOLDOWN {
+        return;
+    }
+
+    if let Ok((player, stats, player_transform)) = player_query.get_single() {
+        let size = stats.get(Stat::Size);
+
+        // Only the strongest enemy touching the player hurts it
+        let strongest = enemy_query
+            .iter()
+            .filter(|(_, enemy, enemy_transform)| {
+                player_transform
+                    .translation
+                    .distance(enemy_transform.translation)
+                    < enemy.size + size
+            })
+            .max_by(|(_, a, _), (_, b, _)| a.contact_damage.total_cmp(&b.contact_damage));
+
+        if let Some((source, enemy, _)) = strongest {
+            *since_last_hit = 0.;
+
+            damage_writer.send(DamageEvent {
+                entity: player,
+                damage: enemy.contact_damage,
+                damage_type: DamageType::Kinetic,
+                source: Some(source),
+                cause: DamageCause::Attack,
+                unmitigated: false,
+                knockback: None,
+            });
+        }
+    }
+}
+
+pub fn player_death_check(
+    player_query: Query<&Health2d, With<Player>>,
+    mut state: ResMut<PhaseState>,
+    mut time: ResMut<Time<Virtual>>,
+    mut game_over_writer: EventWriter<GameOver>,
+) {
+    if state.status == PhaseStatus::GameOver {
+        return;
+    }
+
+    if let Ok(health) = player_query.get_single() {
+        if health.health <= 0. {
+            state.status = PhaseStatus::GameOver;
+            time.pause();
+
+            game_over_writer.send(GameOver);
         }
     }
 }
 
 pub fn player_movement(
     keys: Res<ButtonInput<KeyCode>>,
-    mut player_query: Query<(&Player, &mut Transform), With<Player>>,
+    mut player_query: Query<(&Stats, &mut Transform), With<Player>>,
     time: Res<Time>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     mut player_moved_event: EventWriter<PlayerMovedEvent>,
 ) {
     if let Ok(query) = player_query.get_single_mut() {
-        let (player, mut transform) = query;
+        let (stats, mut transform) = query;
 
         let mut direction = Vec3::ZERO;
 
@@ -65,13 +117,16 @@ pub fn player_movement(
 
         let window = window_query.single();
 
-        let x_min = 0. + player.size;
-        let x_max = window.width() - player.size;
+        let size = stats.get(Stat::Size);
+
+        let x_min = 0. + size;
+        let x_max = window.width() - size;
 
-        let y_min = 0. + player.size;
-        let y_max = window.height() - player.size;
+        let y_min = 0. + size;
+        let y_max = window.height() - size;
 
-        let new_position = transform.translation + direction * player.speed * time.delta_secs();
+        let new_position =
+            transform.translation + direction * stats.get(Stat::Speed) * time.delta_secs();
 
         if new_position.x < x_min || new_position.x > x_max {
             return;
@@ -87,24 +142,37 @@ pub fn player_movement(
 
 pub fn receive_xp_listener(
     mut commands: Commands,
-    mut player_query: Query<(&mut Player, &mut Transform)>,
+    mut player_query: Query<(&mut Player, &Stats, &Transform)>,
     mut event_reader: EventReader<PlayerReceiveXpEvent>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut level_up_writer: EventWriter<PlayerLevelUpEvent>,
 ) {
     for event in event_reader.read() {
-        if let Ok((mut player, position)) = player_query.get_single_mut() {
+        if let Ok((mut player, stats, position)) = player_query.get_single_mut() {
             player.xp += event.xp;
 
-            let mut new_position = position.clone();
-            new_position.translation.y += player.size * 1.5;
+            while player.level < Player::level_for_xp(player.xp) {
+                player.level += 1;
+
+                level_up_writer.send(PlayerLevelUpEvent);
+            }
+
+            let mut new_position = *position;
+            new_position.translation.y += stats.get(Stat::Size) * 1.5;
 
             commands.spawn((
-                MoveAndFade {
-                    speed: 50.,
-                    direction: Vec2::new(0., 1.),
-                    deceleration: 1.,
-                    timer: Timer::from_seconds(0.75, TimerMode::Once),
-                },
+                Tween::new(TweenStep {
+                    lenses: vec![
+                        Lens::Translation {
+                            from: new_position.translation,
+                            to: new_position.translation + Vec3::Y * 30.,
+                        },
+                        Lens::Alpha { from: 1., to: 0. },
+                    ],
+                    duration: 0.75,
+                    ease: Ease::CubicOut,
+                    ..default()
+                })
+                .with_on_complete(OnComplete::Despawn),
                 new_position,
                 Text2d::new(format!("+{:0}", event.xp)),
                 TextFont {
@@ -112,7 +180,6 @@ pub fn receive_xp_listener(
                     ..default()
                 },
                 TextColor(Color::srgb(5.5, 0., 5.5)),
-                MeshMaterial2d(materials.add(Color::srgb(7.5, 7.5, 0.))),
             ));
         }
     }
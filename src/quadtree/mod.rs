@@ -123,6 +123,18 @@ impl<TKey: Eq + Hash + Clone, T: Clone> QuadTree<TKey, T> {
         }
     }
 
+    /// Removes every item, keeping the leaves.
+    pub fn clear(&mut self) {
+        match self {
+            QuadTree::Leaf { items, .. } => items.clear(),
+            QuadTree::Node { children, .. } => {
+                for child in children.iter_mut() {
+                    child.clear();
+                }
+            }
+        }
+    }
+
     pub fn delete(&mut self, key: TKey, position: Vec2) {
         if let Some(mut items) = self.find(position) {
             items.remove(&key);
@@ -155,6 +167,37 @@ impl<TKey: Eq + Hash + Clone, T: Clone> QuadTree<TKey, T> {
             }
         }
     }
+
+    /// Keys of every item in the leaves touching the square around the circle.
+    ///
+    /// Items are stored without their position, so callers still have to check the distance.
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<TKey> {
+        let mut keys = Vec::new();
+
+        self.collect_in_rect(center - radius, center + radius, &mut keys);
+
+        keys
+    }
+
+    fn collect_in_rect(&self, min: Vec2, max: Vec2, keys: &mut Vec<TKey>) {
+        let bounds = match self {
+            QuadTree::Leaf { bounds, .. } | QuadTree::Node { bounds, .. } => bounds,
+        };
+
+        if max.x < bounds.x || min.x >= bounds.max_x || max.y < bounds.y || min.y >= bounds.max_y {
+            return;
+        }
+
+        match self {
+            QuadTree::Leaf { items, .. } => keys.extend(items.keys().cloned()),
+            QuadTree::Node { children, .. } => {
+                for child in children.iter() {
+                    child.collect_in_rect(min, max, keys);
+                }
+            }
+        }
+    }
+
     fn all_items(&self) -> HashMap<TKey, Arc<Mutex<T>>> {
         match self {
             QuadTree::Leaf { items, .. } => items.clone(),
@@ -184,4 +227,31 @@ mod tests {
 
         println!("{:#?}", tree.all_items());
     }
+
+    #[test]
+    fn query_radius_only_returns_nearby_leaves() {
+        let mut tree = QuadTree::<u32, u32>::new(Bounds::new_simple(100., 100.), Some(2));
+
+        tree.insert(1, 1, Vec2::new(10., 10.));
+        tree.insert(2, 2, Vec2::new(15., 12.));
+        tree.insert(3, 3, Vec2::new(90., 90.));
+
+        let mut keys = tree.query_radius(Vec2::new(12., 12.), 5.);
+        keys.sort();
+
+        assert_eq!(keys, vec![1, 2]);
+        assert_eq!(tree.query_radius(Vec2::new(50., 50.), 100.).len(), 3);
+    }
+
+    #[test]
+    fn clear_empties_every_leaf() {
+        let mut tree = QuadTree::<u32, u32>::new(Bounds::new_simple(100., 100.), Some(2));
+
+        tree.insert(1, 1, Vec2::new(10., 10.));
+        tree.insert(2, 2, Vec2::new(90., 90.));
+        tree.clear();
+        tree.insert(1, 1, Vec2::new(90., 10.));
+
+        assert_eq!(tree.query_radius(Vec2::new(50., 50.), 100.), vec![1]);
+    }
 }
@@ -1,3 +1,9 @@
+use crate::health::status::StatusEffect;
+use crate::health::{DamageCause, DamageEvent, DamageType};
+use crate::particle::ParticleEmitter;
+use crate::phase::phase_running;
+use crate::player::player::Player;
+use crate::stats::{Stat, Stats};
 use bevy::app::{App, Plugin, Update};
 use bevy::asset::Assets;
 use bevy::color::Color;
@@ -12,7 +18,12 @@ use std::time::Instant;
 pub struct Shooter {
     pub fire_rate: f32,
     pub damage: f32,
+    pub damage_type: DamageType,
+    /// Effects applied to whatever the bullets hit.
+    pub on_hit: Vec<StatusEffect>,
     pub direction: Vec2,
+    /// Bullets fired per shot, spread around `direction`.
+    pub projectiles: u32,
 
     pub last_shoot: Instant,
 
@@ -23,34 +34,51 @@ pub struct Shooter {
 #[require(Transform)]
 pub struct Bullet {
     pub damage: f32,
+    pub damage_type: DamageType,
+    /// Entity that shot the bullet.
+    pub source: Option<Entity>,
+    pub on_hit: Vec<StatusEffect>,
     pub direction: Vec2,
     pub speed: f32,
     pub size: f32,
 }
 
+/// Marks bullets shot by enemies, they hurt the player instead of enemies.
+#[derive(Component)]
+pub struct Hostile;
+
 #[derive(Event)]
 pub struct ShootEvent {
     pub damage: f32,
+    pub damage_type: DamageType,
+    pub source: Option<Entity>,
+    /// Dropped for hostile bullets.
+    pub on_hit: Vec<StatusEffect>,
     pub direction: Vec2,
     pub position: Transform,
+    pub hostile: bool,
 }
 
+/// Angle in radians between two bullets of the same shot.
+const PROJECTILE_SPREAD: f32 = 0.15;
+
 pub struct ShotPlugin;
 
 impl Plugin for ShotPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ShootEvent>()
-            .add_systems(Update, shoot_over_time)
+            .add_systems(Update, shoot_over_time.run_if(phase_running))
             .add_systems(Update, shoot_event_listener)
-            .add_systems(Update, move_bullet_and_despawn);
+            .add_systems(Update, move_bullet_and_despawn)
+            .add_systems(Update, hostile_bullet_hit_player);
     }
 }
 
 pub fn shoot_over_time(
-    mut shooter_query: Query<(&mut Shooter, &Transform)>,
+    mut shooter_query: Query<(Entity, &mut Shooter, &Transform)>,
     mut event_writer: EventWriter<ShootEvent>,
 ) {
-    for (mut shooter, transform) in shooter_query.iter_mut() {
+    for (entity, mut shooter, transform) in shooter_query.iter_mut() {
         if shooter.last_shoot.elapsed().as_secs_f32() < (1. / shooter.fire_rate) {
             return;
         }
@@ -61,11 +89,21 @@ pub fn shoot_over_time(
 
         shooter.last_shoot = Instant::now();
 
-        event_writer.send(ShootEvent {
-            damage: shooter.damage,
-            direction: shooter.direction,
-            position: transform.clone(),
-        });
+        let middle = (shooter.projectiles.max(1) - 1) as f32 / 2.;
+
+        for projectile in 0..shooter.projectiles.max(1) {
+            let angle = (projectile as f32 - middle) * PROJECTILE_SPREAD;
+
+            event_writer.send(ShootEvent {
+                damage: shooter.damage,
+                damage_type: shooter.damage_type,
+                source: Some(entity),
+                on_hit: shooter.on_hit.clone(),
+                direction: Vec2::from_angle(angle).rotate(shooter.direction),
+                position: *transform,
+                hostile: false,
+            });
+        }
     }
 }
 
@@ -76,11 +114,36 @@ pub fn shoot_event_listener(
     mut event_reader: EventReader<ShootEvent>,
 ) {
     const BULLET_SIZE: f32 = 2.;
+    const HOSTILE_BULLET_SIZE: f32 = 4.;
 
     for event in event_reader.read() {
+        if event.hostile {
+            commands.spawn((
+                Hostile,
+                Bullet {
+                    damage: event.damage,
+                    damage_type: event.damage_type,
+                    source: event.source,
+                    // The player takes no status effects
+                    on_hit: Vec::new(),
+                    speed: 300.,
+                    size: HOSTILE_BULLET_SIZE,
+                    direction: event.direction,
+                },
+                event.position,
+                Mesh2d(meshes.add(Circle::new(HOSTILE_BULLET_SIZE))),
+                MeshMaterial2d(materials.add(Color::srgb(7.5, 0.5, 0.5))),
+            ));
+
+            continue;
+        }
+
         commands.spawn((
             Bullet {
                 damage: event.damage,
+                damage_type: event.damage_type,
+                source: event.source,
+                on_hit: event.on_hit.clone(),
                 speed: 600.,
                 size: BULLET_SIZE,
                 direction: event.direction,
@@ -89,6 +152,11 @@ pub fn shoot_event_listener(
             Mesh2d(meshes.add(Circle::new(BULLET_SIZE))),
             MeshMaterial2d(materials.add(Color::srgb(7.5, 7.5, 0.))),
         ));
+
+        commands.spawn((
+            ParticleEmitter::muzzle_flash(event.direction),
+            event.position,
+        ));
     }
 }
 
@@ -119,3 +187,32 @@ pub fn move_bullet_and_despawn(
         transform.translation += Transform::from_xyz(sum.x, sum.y, 0.).translation;
     }
 }
+
+pub fn hostile_bullet_hit_player(
+    mut commands: Commands,
+    bullet_query: Query<(Entity, &Transform, &Bullet), With<Hostile>>,
+    player_query: Query<(Entity, &Stats, &Transform), With<Player>>,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    let Ok((player, stats, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    for (entity, transform, bullet) in bullet_query.iter() {
+        let distance = transform.translation.distance(player_transform.translation);
+
+        if distance < stats.get(Stat::Size) + bullet.size {
+            commands.entity(entity).despawn_recursive();
+
+            damage_writer.send(DamageEvent {
+                entity: player,
+                damage: bullet.damage,
+                damage_type: bullet.damage_type,
+                source: bullet.source,
+                cause: DamageCause::Attack,
+                unmitigated: false,
+                knockback: None,
+            });
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::phase::SpawnEnemyEvent;
+use crate::player::player::Player;
+use crate::tween::{Ease, Lens, Repeat, RepeatStyle, Tween, TweenStep};
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use rand::Rng;
+use serde::Deserialize;
+use std::f32::consts::TAU;
+
+pub struct SpawnPlugin;
+
+impl Plugin for SpawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnSettings>()
+            .init_resource::<SpawnWarningAssets>()
+            .add_systems(Update, place_spawns)
+            .add_systems(Update, materialize_spawns);
+    }
+}
+
+/// Distance from the view border at which the warning markers are drawn.
+const WARNING_INSET: f32 = 16.;
+const WARNING_SIZE: f32 = 10.;
+
+/// Where enemies without an explicit position appear.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub enum SpawnStrategy {
+    /// Circle around the player, `margin` past the farthest view corner.
+    Ring { margin: f32 },
+    /// Random point on the view border, pushed `margin` outside.
+    Edges { margin: f32 },
+    /// `count` enemies in a line facing the player, placed like [`SpawnStrategy::Edges`].
+    Formation {
+        count: u32,
+        spacing: f32,
+        margin: f32,
+    },
+}
+
+impl Default for SpawnStrategy {
+    fn default() -> Self {
+        SpawnStrategy::Ring { margin: 40. }
+    }
+}
+
+impl SpawnStrategy {
+    /// Enemies spawned by one request.
+    pub fn enemies(&self) -> usize {
+        match self {
+            SpawnStrategy::Formation { count, .. } => *count as usize,
+            _ => 1,
+        }
+    }
+
+    /// Candidate positions for one spawn request, one per enemy.
+    pub fn positions(&self, view: Rect, player: Vec2, rng: &mut impl Rng) -> Vec<Vec2> {
+        match self {
+            SpawnStrategy::Ring { margin } => {
+                let radius = view
+                    .min
+                    .distance(player)
+                    .max(view.max.distance(player))
+                    .max(Vec2::new(view.min.x, view.max.y).distance(player))
+                    .max(Vec2::new(view.max.x, view.min.y).distance(player))
+                    + margin;
+
+                vec![player + Vec2::from_angle(rng.gen_range(0. ..TAU)) * radius]
+            }
+            SpawnStrategy::Edges { margin } => vec![edge_point(view.inflate(*margin), rng)],
+            SpawnStrategy::Formation {
+                count,
+                spacing,
+                margin,
+            } => {
+                let center = edge_point(view.inflate(*margin), rng);
+                let side = (player - center).normalize_or_zero().perp();
+                let half = (*count as f32 - 1.) / 2.;
+
+                (0..*count)
+                    .map(|index| center + side * (index as f32 - half) * spacing)
+                    .collect()
+            }
+        }
+    }
+}
+
+fn edge_point(rect: Rect, rng: &mut impl Rng) -> Vec2 {
+    let size = rect.size();
+    // Picks a point along the perimeter so longer edges get more spawns
+    let mut along = rng.gen_range(0. ..2. * (size.x + size.y));
+
+    if along < size.x {
+        return Vec2::new(rect.min.x + along, rect.min.y);
+    }
+    along -= size.x;
+
+    if along < size.x {
+        return Vec2::new(rect.min.x + along, rect.max.y);
+    }
+    along -= size.x;
+
+    if along < size.y {
+        return Vec2::new(rect.min.x, rect.min.y + along);
+    }
+
+    Vec2::new(rect.max.x, rect.min.y + along - size.y)
+}
+
+#[derive(Resource)]
+pub struct SpawnSettings {
+    /// Used by requests that do not pick a strategy.
+    pub strategy: SpawnStrategy,
+    /// Seconds the warning marker shows before the enemy appears.
+    pub warning_seconds: f32,
+    /// Candidates closer than this to the player are rejected.
+    pub min_player_distance: f32,
+    /// Candidates tried before the spawn is dropped.
+    pub max_attempts: u32,
+}
+
+impl Default for SpawnSettings {
+    fn default() -> Self {
+        Self {
+            strategy: SpawnStrategy::default(),
+            warning_seconds: 0.8,
+            min_player_distance: 200.,
+            max_attempts: 8,
+        }
+    }
+}
+
+impl SpawnSettings {
+    /// Tries up to `max_attempts` candidates, `None` when all of them were too close.
+    pub fn find_positions(
+        &self,
+        strategy: &SpawnStrategy,
+        view: Rect,
+        player: Vec2,
+        rng: &mut impl Rng,
+    ) -> Option<Vec<Vec2>> {
+        (0..self.max_attempts)
+            .map(|_| strategy.positions(view, player, rng))
+            .find(|positions| {
+                positions
+                    .iter()
+                    .all(|position| position.distance(player) >= self.min_player_distance)
+            })
+    }
+}
+
+/// Marker shown where an enemy is about to appear, it holds the request until then.
+#[derive(Component)]
+pub struct SpawnWarning {
+    timer: Timer,
+    event: SpawnEnemyEvent,
+}
+
+impl SpawnWarning {
+    pub fn archetype(&self) -> &str {
+        &self.event.archetype
+    }
+}
+
+#[derive(Resource)]
+pub struct SpawnWarningAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+}
+
+impl FromWorld for SpawnWarningAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Circle::new(1.));
+        let material = world
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(Color::srgba(3., 0.2, 0.2, 0.6));
+
+        Self { mesh, material }
+    }
+}
+
+/// Turns requests without a position into warning markers.
+pub fn place_spawns(
+    mut commands: Commands,
+    mut event_reader: EventReader<SpawnEnemyEvent>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<&Transform, With<Player>>,
+    settings: Res<SpawnSettings>,
+    assets: Res<SpawnWarningAssets>,
+) {
+    let (Ok(window), Ok(player_transform)) = (window_query.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+
+    let view = Rect::new(0., 0., window.width(), window.height());
+    let player = player_transform.translation.truncate();
+
+    for event in event_reader.read() {
+        if event.position.is_some() {
+            continue;
+        }
+
+        let strategy = event.strategy.as_ref().unwrap_or(&settings.strategy);
+
+        let Some(positions) =
+            settings.find_positions(strategy, view, player, &mut rand::thread_rng())
+        else {
+            warn!("No spawn position found for {}", event.archetype);
+            continue;
+        };
+
+        for position in positions {
+            // Off screen spawns are announced on the closest point of the border
+            let marker = position.clamp(view.min + WARNING_INSET, view.max - WARNING_INSET);
+
+            commands.spawn((
+                SpawnWarning {
+                    timer: Timer::from_seconds(settings.warning_seconds, TimerMode::Once),
+                    event: SpawnEnemyEvent {
+                        position: Some(position.extend(0.)),
+                        ..event.clone()
+                    },
+                },
+                Transform::from_translation(marker.extend(0.3))
+                    .with_scale(Vec3::splat(WARNING_SIZE)),
+                Mesh2d(assets.mesh.clone()),
+                MeshMaterial2d(assets.material.clone()),
+                Tween::new(TweenStep {
+                    lenses: vec![Lens::Scale {
+                        from: Vec3::splat(WARNING_SIZE * 0.6),
+                        to: Vec3::splat(WARNING_SIZE),
+                    }],
+                    duration: 0.2,
+                    ease: Ease::SineInOut,
+                    ..default()
+                })
+                .with_repeat(Repeat::Forever, RepeatStyle::PingPong),
+            ));
+        }
+    }
+}
+
+pub fn materialize_spawns(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut warning_query: Query<(Entity, &mut SpawnWarning)>,
+    mut spawn_enemy_event_writter: EventWriter<SpawnEnemyEvent>,
+) {
+    for (entity, mut warning) in warning_query.iter_mut() {
+        if !warning.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        spawn_enemy_event_writter.send(warning.event.clone());
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEW: Rect = Rect {
+        min: Vec2::ZERO,
+        max: Vec2::new(800., 600.),
+    };
+
+    #[test]
+    fn ring_spawns_outside_view() {
+        let strategy = SpawnStrategy::Ring { margin: 10. };
+
+        for _ in 0..100 {
+            let positions =
+                strategy.positions(VIEW, Vec2::new(100., 100.), &mut rand::thread_rng());
+
+            assert!(positions.iter().all(|position| !VIEW.contains(*position)));
+        }
+    }
+
+    #[test]
+    fn formation_spawns_every_enemy_outside_view() {
+        let strategy = SpawnStrategy::Formation {
+            count: 5,
+            spacing: 20.,
+            margin: 100.,
+        };
+
+        let positions = strategy.positions(VIEW, VIEW.center(), &mut rand::thread_rng());
+
+        assert_eq!(positions.len(), 5);
+        assert!(positions.iter().all(|position| !VIEW.contains(*position)));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let settings = SpawnSettings {
+            min_player_distance: 10_000.,
+            ..default()
+        };
+
+        let positions = settings.find_positions(
+            &SpawnStrategy::Edges { margin: 0. },
+            VIEW,
+            VIEW.center(),
+            &mut rand::thread_rng(),
+        );
+
+        assert_eq!(positions, None);
+    }
+}
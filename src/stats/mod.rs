@@ -0,0 +1,270 @@
+use crate::health::{CriticalStrike, Health2d, Shield};
+use crate::shot::Shooter;
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tick_stat_modifiers)
+            .add_systems(Update, apply_stats_to_shooter.after(tick_stat_modifiers))
+            .add_systems(Update, apply_stats_to_health.after(tick_stat_modifiers))
+            .add_systems(Update, apply_stats_to_critical.after(tick_stat_modifiers))
+            .add_systems(Update, apply_stats_to_size.after(tick_stat_modifiers));
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Stat {
+    MaxHealth,
+    Size,
+    FireRate,
+    Damage,
+    Projectiles,
+    Speed,
+    /// Distance from which pickups start flying to the entity.
+    MagnetRadius,
+    /// Chance, between 0 and 1, for a hit to be critical.
+    CritChance,
+    CritMultiplier,
+    /// Shield that absorbs damage first, the entity has none at 0.
+    MaxShield,
+    /// Damage subtracted from every hit taken.
+    Armour,
+    /// Health restored every second.
+    Regeneration,
+}
+
+impl Stat {
+    fn min_value(self) -> f32 {
+        match self {
+            Stat::MaxHealth => 1.,
+            Stat::Size => 8.,
+            Stat::FireRate => 0.1,
+            Stat::Damage => 0.,
+            Stat::Projectiles => 1.,
+            Stat::Speed => 0.,
+            Stat::MagnetRadius => 0.,
+            Stat::CritChance => 0.,
+            Stat::CritMultiplier => 1.,
+            Stat::MaxShield => 0.,
+            Stat::Armour => 0.,
+            Stat::Regeneration => 0.,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ModifierKind {
+    /// Added to the base value.
+    Add,
+    /// Multiplies the base value plus every `Add` modifier.
+    Multiply,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub kind: ModifierKind,
+    pub value: f32,
+}
+
+struct ActiveModifier {
+    modifier: StatModifier,
+    /// What applied the modifier, e.g. an upgrade id or a buff name.
+    source: String,
+    /// Permanent when `None`.
+    timer: Option<Timer>,
+}
+
+/// Base values plus modifiers from upgrades, buffs and debuffs.
+///
+/// Gameplay code reads the derived values through [`Stats::get`] instead of the base ones.
+#[derive(Component, Default)]
+pub struct Stats {
+    base: HashMap<Stat, f32>,
+    modifiers: Vec<ActiveModifier>,
+}
+
+impl Stats {
+    pub fn new(base: impl IntoIterator<Item = (Stat, f32)>) -> Self {
+        Self {
+            base: base.into_iter().collect(),
+            modifiers: Vec::new(),
+        }
+    }
+
+    pub fn base(&self, stat: Stat) -> f32 {
+        self.base.get(&stat).copied().unwrap_or(0.)
+    }
+
+    pub fn get(&self, stat: Stat) -> f32 {
+        let (add, multiply) = self
+            .modifiers
+            .iter()
+            .filter(|active| active.modifier.stat == stat)
+            .fold((0., 1.), |(add, multiply), active| {
+                match active.modifier.kind {
+                    ModifierKind::Add => (add + active.modifier.value, multiply),
+                    ModifierKind::Multiply => (add, multiply * active.modifier.value),
+                }
+            });
+
+        ((self.base(stat) + add) * multiply).max(stat.min_value())
+    }
+
+    pub fn add_modifier(&mut self, modifier: StatModifier, source: &str) {
+        self.modifiers.push(ActiveModifier {
+            modifier,
+            source: source.to_string(),
+            timer: None,
+        });
+    }
+
+    /// Adds a modifier that expires, applying the same source again refreshes its duration.
+    pub fn add_timed_modifier(&mut self, modifier: StatModifier, source: &str, duration: Duration) {
+        let existing = self.modifiers.iter_mut().find(|active| {
+            active.source == source
+                && active.modifier.stat == modifier.stat
+                && active.modifier.kind == modifier.kind
+                && active.timer.is_some()
+        });
+
+        match existing {
+            Some(active) => {
+                active.modifier = modifier;
+                active.timer = Some(Timer::new(duration, TimerMode::Once));
+            }
+            None => self.modifiers.push(ActiveModifier {
+                modifier,
+                source: source.to_string(),
+                timer: Some(Timer::new(duration, TimerMode::Once)),
+            }),
+        }
+    }
+
+    /// Returns whether a modifier expired.
+    fn tick(&mut self, delta: Duration) -> bool {
+        let count = self.modifiers.len();
+
+        self.modifiers.retain_mut(|active| match &mut active.timer {
+            Some(timer) => !timer.tick(delta).finished(),
+            None => true,
+        });
+
+        self.modifiers.len() != count
+    }
+}
+
+/// Only flags the stats as changed when a modifier expires, the derived values are pushed to
+/// the other components on changes.
+pub fn tick_stat_modifiers(time: Res<Time>, mut stats_query: Query<&mut Stats>) {
+    for mut stats in stats_query.iter_mut() {
+        if stats.bypass_change_detection().tick(time.delta()) {
+            stats.set_changed();
+        }
+    }
+}
+
+pub fn apply_stats_to_shooter(mut query: Query<(&Stats, &mut Shooter), Changed<Stats>>) {
+    for (stats, mut shooter) in query.iter_mut() {
+        shooter.fire_rate = stats.get(Stat::FireRate);
+        shooter.damage = stats.get(Stat::Damage);
+        shooter.projectiles = stats.get(Stat::Projectiles).round() as u32;
+    }
+}
+
+pub fn apply_stats_to_critical(mut query: Query<(&Stats, &mut CriticalStrike), Changed<Stats>>) {
+    for (stats, mut critical_strike) in query.iter_mut() {
+        critical_strike.chance = stats.get(Stat::CritChance).min(1.);
+        critical_strike.multiplier = stats.get(Stat::CritMultiplier);
+    }
+}
+
+pub fn apply_stats_to_health(mut query: Query<(&Stats, &mut Health2d), Changed<Stats>>) {
+    for (stats, mut health) in query.iter_mut() {
+        let max_health = stats.get(Stat::MaxHealth);
+
+        // Raising the max health heals by the same amount, lowering it only caps the health
+        if health.max_health != max_health {
+            let gained = (max_health - health.max_health).max(0.);
+
+            health.health = (health.health + gained).min(max_health);
+            health.max_health = max_health;
+        }
+
+        let max_shield = stats.get(Stat::MaxShield);
+
+        if max_shield <= 0. {
+            health.shield = None;
+        } else if let Some(shield) = health.shield.as_mut() {
+            shield.set_max(max_shield);
+        } else {
+            health.shield = Some(Shield::new(max_shield));
+        }
+
+        health.armour = stats.get(Stat::Armour);
+        health.regeneration = stats.get(Stat::Regeneration);
+    }
+}
+
+/// Scales the entity mesh, which is built with the base size, to the derived size.
+pub fn apply_stats_to_size(mut query: Query<(&Stats, &mut Transform), Changed<Stats>>) {
+    for (stats, mut transform) in query.iter_mut() {
+        let base = stats.base(Stat::Size);
+
+        if base > 0. {
+            transform.scale = Vec3::splat(stats.get(Stat::Size) / base);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modifier(stat: Stat, kind: ModifierKind, value: f32) -> StatModifier {
+        StatModifier { stat, kind, value }
+    }
+
+    #[test]
+    fn modifiers_add_then_multiply() {
+        let mut stats = Stats::new([(Stat::Speed, 100.), (Stat::Damage, 1.)]);
+
+        stats.add_modifier(modifier(Stat::Speed, ModifierKind::Multiply, 1.5), "a");
+        stats.add_modifier(modifier(Stat::Speed, ModifierKind::Add, 20.), "b");
+        stats.add_modifier(modifier(Stat::Speed, ModifierKind::Multiply, 2.), "c");
+
+        assert_eq!(stats.base(Stat::Speed), 100.);
+        assert_eq!(stats.get(Stat::Speed), 360.);
+        assert_eq!(stats.get(Stat::Damage), 1.);
+    }
+
+    #[test]
+    fn derived_value_is_clamped() {
+        let mut stats = Stats::new([(Stat::Size, 20.)]);
+
+        stats.add_modifier(modifier(Stat::Size, ModifierKind::Multiply, 0.), "shrink");
+
+        assert_eq!(stats.get(Stat::Size), Stat::Size.min_value());
+    }
+
+    #[test]
+    fn timed_modifier_expires_and_refreshes() {
+        let mut stats = Stats::new([(Stat::Speed, 100.)]);
+        let haste = modifier(Stat::Speed, ModifierKind::Multiply, 2.);
+
+        stats.add_timed_modifier(haste, "haste", Duration::from_secs(2));
+        stats.tick(Duration::from_secs(1));
+        stats.add_timed_modifier(haste, "haste", Duration::from_secs(2));
+        assert!(!stats.tick(Duration::from_secs(1)));
+        assert_eq!(stats.get(Stat::Speed), 200.);
+
+        assert!(stats.tick(Duration::from_secs(1)));
+        assert_eq!(stats.get(Stat::Speed), 100.);
+    }
+}
@@ -0,0 +1,372 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::asset::Assets;
+use bevy::color::{Alpha, Color, Mix};
+use bevy::math::{Quat, Vec3};
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TweenCompleted>()
+            .add_systems(Update, animate_tweens);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum Ease {
+    #[default]
+    Linear,
+    CubicOut,
+    SineInOut,
+    BackOut,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+
+        match self {
+            Ease::Linear => t,
+            Ease::CubicOut => 1. - (1. - t).powi(3),
+            Ease::SineInOut => -((PI * t).cos() - 1.) / 2.,
+            Ease::BackOut => {
+                const C1: f32 = 1.70158;
+                const C3: f32 = C1 + 1.;
+
+                1. + C3 * (t - 1.).powi(3) + C1 * (t - 1.).powi(2)
+            }
+        }
+    }
+}
+
+/// A single property animated between two values.
+#[derive(Clone, Debug)]
+pub enum Lens {
+    Translation {
+        from: Vec3,
+        to: Vec3,
+    },
+    Scale {
+        from: Vec3,
+        to: Vec3,
+    },
+    /// Rotation around the z axis in radians.
+    Rotation {
+        from: f32,
+        to: f32,
+    },
+    /// Applied to the `ColorMaterial` and to the `TextColor` of the entity.
+    Color {
+        from: Color,
+        to: Color,
+    },
+    /// Same targets as `Color` but only touches the alpha channel.
+    Alpha {
+        from: f32,
+        to: f32,
+    },
+}
+
+/// Lenses animated together for `duration` seconds after waiting `delay` seconds.
+#[derive(Clone, Default, Debug)]
+pub struct TweenStep {
+    pub lenses: Vec<Lens>,
+    pub duration: f32,
+    pub delay: f32,
+    pub ease: Ease,
+}
+
+impl TweenStep {
+    fn length(&self) -> f32 {
+        self.delay + self.duration
+    }
+
+    fn progress(&self, local: f32) -> f32 {
+        if self.duration <= 0. {
+            return if local >= self.delay { 1. } else { 0. };
+        }
+
+        self.ease.apply((local - self.delay) / self.duration)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Repeat {
+    Once,
+    Forever,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RepeatStyle {
+    Restart,
+    /// Plays the sequence backwards every other loop.
+    PingPong,
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum OnComplete {
+    #[default]
+    Nothing,
+    Remove,
+    Despawn,
+}
+
+#[derive(Event)]
+pub struct TweenCompleted {
+    pub entity: Entity,
+    pub tag: u32,
+}
+
+/// Plays a sequence of [`TweenStep`]s on the entity it is attached to.
+#[derive(Component, Clone, Debug)]
+pub struct Tween {
+    pub steps: Vec<TweenStep>,
+    pub repeat: Repeat,
+    pub style: RepeatStyle,
+    pub on_complete: OnComplete,
+    /// Sent back in [`TweenCompleted`] so listeners can tell tweens apart.
+    pub tag: u32,
+
+    step: usize,
+    local: f32,
+    backwards: bool,
+    finished: bool,
+}
+
+impl Tween {
+    pub fn new(step: TweenStep) -> Self {
+        Self::sequence(vec![step])
+    }
+
+    pub fn sequence(steps: Vec<TweenStep>) -> Self {
+        Self {
+            steps,
+            repeat: Repeat::Once,
+            style: RepeatStyle::Restart,
+            on_complete: OnComplete::Nothing,
+            tag: 0,
+            step: 0,
+            local: 0.,
+            backwards: false,
+            finished: false,
+        }
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat, style: RepeatStyle) -> Self {
+        self.repeat = repeat;
+        self.style = style;
+        self
+    }
+
+    pub fn with_on_complete(mut self, on_complete: OnComplete) -> Self {
+        self.on_complete = on_complete;
+        self
+    }
+
+    pub fn with_tag(mut self, tag: u32) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Advances the tween and returns the step being played with its eased progress.
+    fn tick(&mut self, delta_secs: f32) -> Option<(usize, f32)> {
+        if self.finished || self.steps.is_empty() {
+            return None;
+        }
+
+        self.local += delta_secs;
+
+        while self.local >= self.steps[self.step].length() {
+            self.local -= self.steps[self.step].length();
+
+            let last = if self.backwards {
+                self.step == 0
+            } else {
+                self.step == self.steps.len() - 1
+            };
+
+            if !last {
+                if self.backwards {
+                    self.step -= 1;
+                } else {
+                    self.step += 1;
+                }
+                continue;
+            }
+
+            let again = match self.repeat {
+                Repeat::Once => false,
+                Repeat::Forever => true,
+            };
+
+            if !again {
+                self.finished = true;
+                return Some((self.step, if self.backwards { 0. } else { 1. }));
+            }
+
+            if self.style == RepeatStyle::PingPong {
+                self.backwards = !self.backwards;
+            } else {
+                self.step = 0;
+            }
+
+            if self.steps.iter().all(|step| step.length() <= 0.) {
+                self.finished = true;
+                return Some((self.step, 1.));
+            }
+        }
+
+        let step = &self.steps[self.step];
+        let local = if self.backwards {
+            step.length() - self.local
+        } else {
+            self.local
+        };
+
+        Some((self.step, step.progress(local)))
+    }
+}
+
+type TweenTarget<'a> = (
+    Entity,
+    &'a mut Tween,
+    Option<&'a mut Transform>,
+    Option<&'a MeshMaterial2d<ColorMaterial>>,
+    Option<&'a mut TextColor>,
+);
+
+fn apply_lens(
+    lens: &Lens,
+    t: f32,
+    transform: Option<&mut Transform>,
+    material: Option<&mut ColorMaterial>,
+    text_color: Option<&mut TextColor>,
+) {
+    match lens {
+        Lens::Translation { from, to } => {
+            if let Some(transform) = transform {
+                transform.translation = from.lerp(*to, t);
+            }
+        }
+        Lens::Scale { from, to } => {
+            if let Some(transform) = transform {
+                transform.scale = from.lerp(*to, t);
+            }
+        }
+        Lens::Rotation { from, to } => {
+            if let Some(transform) = transform {
+                transform.rotation = Quat::from_rotation_z(from + (to - from) * t);
+            }
+        }
+        Lens::Color { from, to } => {
+            let color = from.mix(to, t);
+
+            if let Some(material) = material {
+                material.color = color;
+            }
+            if let Some(text_color) = text_color {
+                text_color.0 = color;
+            }
+        }
+        Lens::Alpha { from, to } => {
+            let alpha = from + (to - from) * t;
+
+            if let Some(material) = material {
+                material.color.set_alpha(alpha);
+            }
+            if let Some(text_color) = text_color {
+                text_color.0.set_alpha(alpha);
+            }
+        }
+    }
+}
+
+pub fn animate_tweens(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut event_writer: EventWriter<TweenCompleted>,
+    mut tween_query: Query<TweenTarget>,
+) {
+    for (entity, mut tween, mut transform, material, mut text_color) in tween_query.iter_mut() {
+        let Some((step, t)) = tween.tick(time.delta_secs()) else {
+            continue;
+        };
+
+        let mut color_material = material.and_then(|material| materials.get_mut(material.id()));
+
+        for lens in tween.steps[step].lenses.iter() {
+            apply_lens(
+                lens,
+                t,
+                transform.as_deref_mut(),
+                color_material.as_deref_mut(),
+                text_color.as_deref_mut(),
+            );
+        }
+
+        if tween.finished() {
+            event_writer.send(TweenCompleted {
+                entity,
+                tag: tween.tag,
+            });
+
+            match tween.on_complete {
+                OnComplete::Nothing => {}
+                OnComplete::Remove => {
+                    commands.entity(entity).remove::<Tween>();
+                }
+                OnComplete::Despawn => {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(duration: f32) -> TweenStep {
+        TweenStep {
+            duration,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn ease_keeps_end_points() {
+        for ease in [Ease::Linear, Ease::CubicOut, Ease::SineInOut, Ease::BackOut] {
+            assert!(ease.apply(0.).abs() < 1e-5, "{:?}", ease);
+            assert!((ease.apply(1.) - 1.).abs() < 1e-5, "{:?}", ease);
+        }
+    }
+
+    #[test]
+    fn tween_plays_sequence_then_finishes() {
+        let mut tween = Tween::sequence(vec![step(1.), step(1.)]);
+
+        assert_eq!(tween.tick(0.5), Some((0, 0.5)));
+        assert_eq!(tween.tick(1.), Some((1, 0.5)));
+        assert_eq!(tween.tick(1.), Some((1, 1.)));
+        assert!(tween.finished());
+        assert_eq!(tween.tick(1.), None);
+    }
+
+    #[test]
+    fn tween_ping_pong_plays_backwards() {
+        let mut tween = Tween::new(step(1.)).with_repeat(Repeat::Forever, RepeatStyle::PingPong);
+
+        assert_eq!(tween.tick(0.25), Some((0, 0.25)));
+        assert_eq!(tween.tick(1.), Some((0, 0.75)));
+        assert_eq!(tween.tick(1.), Some((0, 0.25)));
+        assert!(!tween.finished());
+    }
+}
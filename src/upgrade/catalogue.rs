@@ -0,0 +1,208 @@
+use crate::health::status::StatusEffect;
+use crate::stats::StatModifier;
+use bevy::color::Color;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Every upgrade the level-up picker can offer, loaded from a `.upgrades.ron` file.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct UpgradeCatalogue {
+    pub upgrades: Vec<UpgradeDefinition>,
+}
+
+#[derive(Resource)]
+pub struct UpgradeCatalogueHandle(pub Handle<UpgradeCatalogue>);
+
+/// The loaded catalogue, `None` until the asset server finished loading it.
+#[derive(SystemParam)]
+pub struct CurrentUpgradeCatalogue<'w> {
+    handle: Res<'w, UpgradeCatalogueHandle>,
+    catalogues: Res<'w, Assets<UpgradeCatalogue>>,
+}
+
+impl CurrentUpgradeCatalogue<'_> {
+    pub fn get(&self) -> Option<&UpgradeCatalogue> {
+        self.catalogues.get(&self.handle.0)
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    pub fn weight(self) -> u32 {
+        match self {
+            Rarity::Common => 100,
+            Rarity::Uncommon => 45,
+            Rarity::Rare => 15,
+            Rarity::Legendary => 5,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Rarity::Common => Color::srgb(0.6, 0.6, 0.6),
+            Rarity::Uncommon => Color::srgb(0.2, 0.8, 0.3),
+            Rarity::Rare => Color::srgb(0.2, 0.5, 1.),
+            Rarity::Legendary => Color::srgb(1., 0.6, 0.1),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct UpgradeDefinition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub rarity: Rarity,
+    /// How many times the upgrade can be taken, unlimited when `None`.
+    #[serde(default)]
+    pub max_stacks: Option<u32>,
+    /// Ids of upgrades that must be taken at least once before this one is offered.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    #[serde(default)]
+    pub modifiers: Vec<StatModifier>,
+    /// Effects the player bullets apply on hit once the upgrade is taken.
+    #[serde(default)]
+    pub on_hit: Vec<StatusEffect>,
+}
+
+impl UpgradeDefinition {
+    pub fn available(&self, acquired: &AcquiredUpgrades) -> bool {
+        let stacks_left = self
+            .max_stacks
+            .is_none_or(|max_stacks| acquired.stacks(&self.id) < max_stacks);
+
+        stacks_left && self.requires.iter().all(|id| acquired.stacks(id) > 0)
+    }
+}
+
+/// How many times each upgrade was taken during the run.
+#[derive(Resource, Default)]
+pub struct AcquiredUpgrades(HashMap<String, u32>);
+
+impl AcquiredUpgrades {
+    pub fn stacks(&self, id: &str) -> u32 {
+        self.0.get(id).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, id: &str) {
+        *self.0.entry(id.to_string()).or_insert(0) += 1;
+    }
+}
+
+/// Draws up to `count` distinct available upgrades weighted by rarity, returns their indexes.
+pub fn pick_upgrades(
+    catalogue: &UpgradeCatalogue,
+    acquired: &AcquiredUpgrades,
+    count: usize,
+    rng: &mut impl Rng,
+) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..catalogue.upgrades.len())
+        .filter(|index| catalogue.upgrades[*index].available(acquired))
+        .collect();
+
+    let mut picked = Vec::new();
+
+    while picked.len() < count && !candidates.is_empty() {
+        let weights = candidates
+            .iter()
+            .map(|index| catalogue.upgrades[*index].rarity.weight());
+
+        let Ok(distribution) = WeightedIndex::new(weights) else {
+            break;
+        };
+
+        picked.push(candidates.swap_remove(distribution.sample(rng)));
+    }
+
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{ModifierKind, Stat};
+
+    fn upgrade(id: &str, max_stacks: Option<u32>, requires: &[&str]) -> UpgradeDefinition {
+        UpgradeDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            rarity: Rarity::Common,
+            max_stacks,
+            requires: requires.iter().map(|id| id.to_string()).collect(),
+            modifiers: vec![StatModifier {
+                stat: Stat::Damage,
+                kind: ModifierKind::Add,
+                value: 1.,
+            }],
+            on_hit: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn pick_respects_stacks_and_prerequisites() {
+        let catalogue = UpgradeCatalogue {
+            upgrades: vec![
+                upgrade("base", Some(1), &[]),
+                upgrade("unlimited", None, &[]),
+                upgrade("advanced", None, &["base"]),
+            ],
+        };
+        let mut acquired = AcquiredUpgrades::default();
+        let mut rng = rand::thread_rng();
+
+        let mut picked = pick_upgrades(&catalogue, &acquired, 3, &mut rng);
+        picked.sort();
+        assert_eq!(picked, vec![0, 1]);
+
+        acquired.add("base");
+
+        let mut picked = pick_upgrades(&catalogue, &acquired, 3, &mut rng);
+        picked.sort();
+        assert_eq!(picked, vec![1, 2]);
+    }
+
+    #[test]
+    fn bundled_catalogue_parses() {
+        let catalogue: UpgradeCatalogue =
+            bevy::asset::ron::de::from_str(include_str!("../../assets/player.upgrades.ron"))
+                .unwrap();
+
+        for upgrade in catalogue.upgrades.iter() {
+            for id in upgrade.requires.iter() {
+                assert!(catalogue.upgrades.iter().any(|other| &other.id == id));
+            }
+        }
+    }
+
+    #[test]
+    fn pick_never_repeats_an_upgrade() {
+        let catalogue = UpgradeCatalogue {
+            upgrades: (0..5)
+                .map(|index| upgrade(&index.to_string(), None, &[]))
+                .collect(),
+        };
+        let acquired = AcquiredUpgrades::default();
+
+        let mut picked = pick_upgrades(&catalogue, &acquired, 3, &mut rand::thread_rng());
+        picked.sort();
+        picked.dedup();
+
+        assert_eq!(picked.len(), 3);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::particle::ParticleEmitter;
+use crate::phase::{PhaseState, PhaseStatus};
+use crate::player::player::Player;
+use crate::player::PlayerLevelUpEvent;
+use crate::shot::Shooter;
+use crate::stats::Stats;
+use crate::utils::ron_asset::RonAssetLoader;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::color::Color;
+use bevy::input::ButtonInput;
+use bevy::prelude::*;
+use catalogue::*;
+
+pub mod catalogue;
+
+pub struct UpgradePlugin;
+
+impl Plugin for UpgradePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UpgradeChosenEvent>()
+            .init_asset::<UpgradeCatalogue>()
+            .register_asset_loader(RonAssetLoader::<UpgradeCatalogue>::new(&["upgrades.ron"]))
+            .init_resource::<PendingLevelUps>()
+            .init_resource::<AcquiredUpgrades>()
+            .add_systems(Startup, load_upgrade_catalogue)
+            .add_systems(Update, queue_level_ups)
+            .add_systems(Update, open_upgrade_menu.after(queue_level_ups))
+            .add_systems(Update, choose_upgrade)
+            .add_systems(Update, upgrade_chosen_listener.after(choose_upgrade));
+    }
+}
+
+const CHOICES: usize = 3;
+
+/// Level ups still waiting for the player to pick an upgrade.
+#[derive(Resource, Default)]
+pub struct PendingLevelUps(pub u32);
+
+#[derive(Event)]
+pub struct UpgradeChosenEvent {
+    pub id: String,
+}
+
+#[derive(Component)]
+pub struct UpgradeMenu;
+
+#[derive(Component)]
+pub struct UpgradeCard {
+    slot: usize,
+    id: String,
+}
+
+const CHOICE_KEYS: [KeyCode; CHOICES] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3];
+
+pub fn load_upgrade_catalogue(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(UpgradeCatalogueHandle(
+        asset_server.load("player.upgrades.ron"),
+    ));
+}
+
+pub fn queue_level_ups(
+    mut commands: Commands,
+    mut pending: ResMut<PendingLevelUps>,
+    mut event_reader: EventReader<PlayerLevelUpEvent>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    for _ in event_reader.read() {
+        pending.0 += 1;
+
+        if let Ok(player) = player_query.get_single() {
+            commands
+                .entity(player)
+                .with_child(ParticleEmitter::level_up());
+        }
+    }
+}
+
+fn spawn_upgrade_menu(
+    commands: &mut Commands,
+    upgrades: Vec<&UpgradeDefinition>,
+    acquired: &AcquiredUpgrades,
+) {
+    commands
+        .spawn((
+            UpgradeMenu,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.7)),
+        ))
+        .with_children(|menu| {
+            menu.spawn((
+                Text::new("Level up! Choose an upgrade"),
+                TextFont {
+                    font_size: 32.,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            menu.spawn(Node {
+                column_gap: Val::Px(24.),
+                ..default()
+            })
+            .with_children(|row| {
+                for (slot, upgrade) in upgrades.into_iter().enumerate() {
+                    let stacks = acquired.stacks(&upgrade.id);
+
+                    row.spawn((
+                        UpgradeCard {
+                            slot,
+                            id: upgrade.id.clone(),
+                        },
+                        Button,
+                        Node {
+                            width: Val::Px(200.),
+                            padding: UiRect::all(Val::Px(16.)),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(8.),
+                            border: UiRect::all(Val::Px(2.)),
+                            ..default()
+                        },
+                        BorderColor(upgrade.rarity.color()),
+                        BackgroundColor(Color::srgb(0.1, 0.05, 0.15)),
+                    ))
+                    .with_children(|card| {
+                        card.spawn((
+                            Text::new(upgrade.name.clone()),
+                            TextFont {
+                                font_size: 22.,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                        card.spawn((
+                            Text::new(format!("{:?}", upgrade.rarity)),
+                            TextFont {
+                                font_size: 14.,
+                                ..default()
+                            },
+                            TextColor(upgrade.rarity.color()),
+                        ));
+                        card.spawn((
+                            Text::new(upgrade.description.clone()),
+                            TextFont {
+                                font_size: 16.,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                        ));
+                        let stacks = match upgrade.max_stacks {
+                            Some(max_stacks) => format!(" {}/{}", stacks, max_stacks),
+                            None if stacks > 0 => format!(" x{}", stacks),
+                            None => String::new(),
+                        };
+
+                        card.spawn((
+                            Text::new(format!("[{}]{}", slot + 1, stacks)),
+                            TextFont {
+                                font_size: 14.,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                        ));
+                    });
+                }
+            });
+        });
+}
+
+pub fn open_upgrade_menu(
+    mut commands: Commands,
+    mut pending: ResMut<PendingLevelUps>,
+    menu_query: Query<(), With<UpgradeMenu>>,
+    mut state: ResMut<PhaseState>,
+    mut time: ResMut<Time<Virtual>>,
+    catalogue: CurrentUpgradeCatalogue,
+    acquired: Res<AcquiredUpgrades>,
+) {
+    if pending.0 == 0 || !menu_query.is_empty() || state.status == PhaseStatus::GameOver {
+        return;
+    }
+
+    let Some(catalogue) = catalogue.get() else {
+        return;
+    };
+
+    let picked = pick_upgrades(catalogue, &acquired, CHOICES, &mut rand::thread_rng());
+
+    // Everything is maxed out, nothing to choose from
+    if picked.is_empty() {
+        pending.0 = 0;
+        return;
+    }
+
+    state.status = PhaseStatus::Paused;
+    time.pause();
+
+    let upgrades = picked
+        .into_iter()
+        .map(|index| &catalogue.upgrades[index])
+        .collect();
+
+    spawn_upgrade_menu(&mut commands, upgrades, &acquired);
+}
+
+pub fn choose_upgrade(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    card_query: Query<(&UpgradeCard, &Interaction)>,
+    menu_query: Query<Entity, With<UpgradeMenu>>,
+    mut event_writer: EventWriter<UpgradeChosenEvent>,
+) {
+    let Ok(menu) = menu_query.get_single() else {
+        return;
+    };
+
+    let chosen = card_query.iter().find_map(|(card, interaction)| {
+        let pressed =
+            *interaction == Interaction::Pressed || keys.just_pressed(CHOICE_KEYS[card.slot]);
+
+        pressed.then(|| card.id.clone())
+    });
+
+    if let Some(id) = chosen {
+        commands.entity(menu).despawn_recursive();
+
+        event_writer.send(UpgradeChosenEvent { id });
+    }
+}
+
+pub fn upgrade_chosen_listener(
+    mut event_reader: EventReader<UpgradeChosenEvent>,
+    mut player_query: Query<(&mut Stats, &mut Shooter), With<Player>>,
+    mut pending: ResMut<PendingLevelUps>,
+    mut state: ResMut<PhaseState>,
+    mut time: ResMut<Time<Virtual>>,
+    catalogue: CurrentUpgradeCatalogue,
+    mut acquired: ResMut<AcquiredUpgrades>,
+) {
+    let catalogue = catalogue.get();
+
+    for event in event_reader.read() {
+        let upgrade = catalogue.and_then(|catalogue| {
+            catalogue
+                .upgrades
+                .iter()
+                .find(|upgrade| upgrade.id == event.id)
+        });
+
+        if let (Some(upgrade), Ok((mut stats, mut shooter))) =
+            (upgrade, player_query.get_single_mut())
+        {
+            for modifier in upgrade.modifiers.iter() {
+                stats.add_modifier(*modifier, &upgrade.id);
+            }
+
+            shooter.on_hit.extend(upgrade.on_hit.iter().copied());
+
+            acquired.add(&upgrade.id);
+        }
+
+        pending.0 = pending.0.saturating_sub(1);
+
+        if pending.0 == 0 {
+            state.status = PhaseStatus::Running;
+            time.unpause();
+        }
+    }
+}
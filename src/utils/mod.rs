@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use rand::Rng;
 
 pub mod bmath;
+pub mod ron_asset;
 
 pub fn random_direction() -> Vec2 {
     let mut rng = rand::thread_rng();
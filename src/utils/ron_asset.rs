@@ -0,0 +1,41 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{ron, Asset, AssetLoader, LoadContext};
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+/// Loads any deserializable asset from a RON file with one of the given extensions.
+pub struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    _asset: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetLoader<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _asset: PhantomData,
+        }
+    }
+}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
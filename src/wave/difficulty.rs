@@ -0,0 +1,195 @@
+use crate::utils::bmath::normal_curve;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Value over the phase time, in seconds.
+#[derive(Deserialize, Clone, Debug)]
+pub enum CurveShape {
+    Constant(f32),
+    /// Goes from `from` at `start` to `to` at `end`, flat before and after.
+    Linear {
+        start: f32,
+        end: f32,
+        from: f32,
+        to: f32,
+    },
+    /// Bell around `mean` that adds `height` on top of `base` at its peak.
+    Normal {
+        mean: f32,
+        std_dev: f32,
+        base: f32,
+        height: f32,
+    },
+    /// Adds the shapes together, to put a bump on top of a ramp for example.
+    Sum(Vec<CurveShape>),
+}
+
+impl CurveShape {
+    pub fn sample(&self, time: f32) -> f32 {
+        match self {
+            CurveShape::Constant(value) => *value,
+            CurveShape::Linear {
+                start,
+                end,
+                from,
+                to,
+            } => {
+                let t = ((time - start) / (end - start).max(f32::EPSILON)).clamp(0., 1.);
+
+                from + (to - from) * t
+            }
+            CurveShape::Normal {
+                mean,
+                std_dev,
+                base,
+                height,
+            } => {
+                let (mean, std_dev) = (*mean as f64, std_dev.max(f32::EPSILON) as f64);
+                // Scaled so the peak is exactly `height`, whatever the deviation
+                let peak = normal_curve(mean, mean, std_dev);
+
+                base + height * (normal_curve(time as f64, mean, std_dev) / peak) as f32
+            }
+            CurveShape::Sum(shapes) => shapes.iter().map(|shape| shape.sample(time)).sum(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Curve {
+    pub shape: CurveShape,
+    #[serde(default)]
+    pub min: f32,
+    #[serde(default = "Curve::unbounded")]
+    pub max: f32,
+}
+
+impl Curve {
+    fn unbounded() -> f32 {
+        f32::MAX
+    }
+
+    pub fn sample(&self, time: f32) -> f32 {
+        self.shape.sample(time).clamp(self.min, self.max)
+    }
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        Self {
+            shape: CurveShape::Constant(1.),
+            min: 0.,
+            max: Curve::unbounded(),
+        }
+    }
+}
+
+/// Multipliers applied on top of the running wave, sampled at the phase time.
+#[derive(Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct Difficulty {
+    /// Divides the threat budget, higher values spawn less often.
+    pub spawn_interval: Curve,
+    pub health: Curve,
+    pub speed: Curve,
+    /// Multiplies the max alive enemies of the wave.
+    pub max_alive: Curve,
+}
+
+/// Keeps the budget from growing without bound whatever the curve says.
+const MIN_SPAWN_INTERVAL: f32 = 0.05;
+
+/// Difficulty at the current phase time, read when enemies spawn.
+#[derive(Resource)]
+pub struct DifficultyScale {
+    pub spawn_interval: f32,
+    pub health: f32,
+    pub speed: f32,
+    pub max_alive: f32,
+}
+
+impl Default for DifficultyScale {
+    fn default() -> Self {
+        Self {
+            spawn_interval: 1.,
+            health: 1.,
+            speed: 1.,
+            max_alive: 1.,
+        }
+    }
+}
+
+impl DifficultyScale {
+    pub fn sample(difficulty: &Difficulty, time: f32) -> Self {
+        Self {
+            spawn_interval: difficulty.spawn_interval.sample(time),
+            health: difficulty.health.sample(time),
+            speed: difficulty.speed.sample(time),
+            max_alive: difficulty.max_alive.sample(time),
+        }
+    }
+
+    /// Threat per second for a wave gaining `budget` per second.
+    pub fn budget(&self, budget: f32) -> f32 {
+        budget / self.spawn_interval.max(MIN_SPAWN_INTERVAL)
+    }
+
+    pub fn max_alive(&self, max_alive: usize) -> usize {
+        (max_alive as f32 * self.max_alive).round() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_is_flat_outside_its_range() {
+        let shape = CurveShape::Linear {
+            start: 10.,
+            end: 20.,
+            from: 1.,
+            to: 3.,
+        };
+
+        assert_eq!(shape.sample(0.), 1.);
+        assert_eq!(shape.sample(15.), 2.);
+        assert_eq!(shape.sample(100.), 3.);
+    }
+
+    #[test]
+    fn normal_peaks_at_height() {
+        let shape = CurveShape::Normal {
+            mean: 300.,
+            std_dev: 20.,
+            base: 1.,
+            height: 0.5,
+        };
+
+        assert!((shape.sample(300.) - 1.5).abs() < 1e-5);
+        assert!((shape.sample(0.) - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn curve_and_budget_are_clamped() {
+        let curve = Curve {
+            shape: CurveShape::Linear {
+                start: 0.,
+                end: 60.,
+                from: 1.,
+                to: -1.,
+            },
+            min: 0.2,
+            max: 1.,
+        };
+
+        assert_eq!(curve.sample(600.), 0.2);
+
+        let scale = DifficultyScale {
+            spawn_interval: 0.,
+            ..default()
+        };
+
+        assert_eq!(scale.budget(1.), 1. / MIN_SPAWN_INTERVAL);
+    }
+}
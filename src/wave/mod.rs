@@ -0,0 +1,446 @@
+use crate::boss::Boss;
+use crate::enemy::archetype::CurrentEnemyArchetypes;
+use crate::enemy::Enemy;
+use crate::phase::{phase_running, PhaseState, SpawnEnemyEvent};
+use crate::player::player::Player;
+use crate::spawn::{SpawnSettings, SpawnStrategy, SpawnWarning};
+use crate::utils::ron_asset::RonAssetLoader;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use difficulty::{Difficulty, DifficultyScale};
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::Rng;
+use serde::Deserialize;
+
+pub mod difficulty;
+
+/// Enemies farther than this many view diagonals from the player are recycled.
+pub const RECYCLE_DISTANCE: f32 = 1.5;
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<WaveScript>()
+            .register_asset_loader(RonAssetLoader::<WaveScript>::new(&["waves.ron"]))
+            .init_resource::<WaveDirector>()
+            .init_resource::<DifficultyScale>()
+            .add_systems(Startup, load_wave_script)
+            .add_systems(Update, update_difficulty.run_if(phase_running))
+            .add_systems(Update, play_scripted_events.run_if(phase_running))
+            .add_systems(
+                Update,
+                spawn_wave_enemies
+                    .after(update_difficulty)
+                    .after(play_scripted_events)
+                    .run_if(phase_running),
+            )
+            .add_systems(Update, recycle_stragglers.run_if(phase_running));
+    }
+}
+
+/// Timeline of a run, loaded from a `.waves.ron` file and reloaded when it changes.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct WaveScript {
+    pub waves: Vec<Wave>,
+    #[serde(default)]
+    pub events: Vec<ScriptedEvent>,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Wave {
+    /// Seconds of gameplay the wave runs between, the last wave to start keeps running past
+    /// its end.
+    pub start: f32,
+    pub end: f32,
+    /// Threat gained per second, spent on archetypes by their cost.
+    pub budget: f32,
+    /// No enemy is spawned while this many are alive, before the difficulty multiplier.
+    pub max_alive: usize,
+    /// Archetype ids and their relative chance to be picked.
+    pub archetypes: Vec<(String, f32)>,
+    #[serde(default)]
+    pub strategy: Option<SpawnStrategy>,
+}
+
+/// Fires when the phase time reaches `at`, then again every `every` seconds when set.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ScriptedEvent {
+    pub at: f32,
+    #[serde(default)]
+    pub every: Option<f32>,
+    pub kind: ScriptedEventKind,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum ScriptedEventKind {
+    /// `count` enemies at once, ignoring the max alive of the wave.
+    Horde {
+        archetype: String,
+        count: u32,
+        #[serde(default)]
+        strategy: Option<SpawnStrategy>,
+    },
+    /// Spawns the boss archetype unless a boss is already alive or about to appear.
+    Boss { archetype: String },
+}
+
+impl WaveScript {
+    /// Index of the wave running at `elapsed`.
+    pub fn wave_at(&self, elapsed: f32) -> Option<usize> {
+        self.waves
+            .iter()
+            .position(|wave| wave.start <= elapsed && elapsed < wave.end)
+            .or_else(|| {
+                self.waves
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, wave)| wave.start <= elapsed)
+                    .max_by(|(_, a), (_, b)| a.start.total_cmp(&b.start))
+                    .map(|(index, _)| index)
+            })
+    }
+}
+
+impl ScriptedEvent {
+    /// Whether the event fires in the phase time window `[from, to)`, at most once even when
+    /// several repetitions fall inside it.
+    pub fn fires_between(&self, from: f32, to: f32) -> bool {
+        let next = match self.every {
+            Some(every) if every > 0. && from > self.at => {
+                self.at + ((from - self.at) / every).ceil() * every
+            }
+            _ => self.at,
+        };
+
+        from <= next && next < to
+    }
+}
+
+impl Wave {
+    pub fn pick_archetype(&self, rng: &mut impl Rng) -> Option<&str> {
+        let distribution =
+            WeightedIndex::new(self.archetypes.iter().map(|(_, weight)| *weight)).ok()?;
+
+        Some(&self.archetypes[distribution.sample(rng)].0)
+    }
+}
+
+#[derive(Resource)]
+pub struct WaveScriptHandle(pub Handle<WaveScript>);
+
+#[derive(SystemParam)]
+pub struct CurrentWaveScript<'w> {
+    handle: Res<'w, WaveScriptHandle>,
+    scripts: Res<'w, Assets<WaveScript>>,
+}
+
+impl CurrentWaveScript<'_> {
+    pub fn get(&self) -> Option<&WaveScript> {
+        self.scripts.get(&self.handle.0)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct WaveDirector {
+    /// Index of the running wave.
+    pub wave: Option<usize>,
+    /// Phase time of the previous update, events in between it and now fire.
+    previous_elapsed: f32,
+    /// Threat gained but not spent yet.
+    budget: f32,
+    /// Archetype picked for the next spawn, kept until the budget affords it so expensive
+    /// archetypes are not skipped for cheaper ones.
+    next: Option<String>,
+}
+
+impl WaveDirector {
+    /// Spends `cost` from the budget, `false` while it cannot be afforded.
+    fn try_spend(&mut self, cost: f32) -> bool {
+        if self.budget < cost {
+            return false;
+        }
+
+        self.budget -= cost;
+        true
+    }
+}
+
+pub fn load_wave_script(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(WaveScriptHandle(asset_server.load("default.waves.ron")));
+}
+
+pub fn update_difficulty(
+    state: Res<PhaseState>,
+    script: CurrentWaveScript,
+    mut scale: ResMut<DifficultyScale>,
+) {
+    if let Some(script) = script.get() {
+        *scale = DifficultyScale::sample(&script.difficulty, state.elapsed);
+    }
+}
+
+pub fn play_scripted_events(
+    state: Res<PhaseState>,
+    script: CurrentWaveScript,
+    mut director: ResMut<WaveDirector>,
+    boss_query: Query<(), With<Boss>>,
+    warning_query: Query<&SpawnWarning>,
+    mut script_events: EventReader<AssetEvent<WaveScript>>,
+    mut spawn_enemy_event_writter: EventWriter<SpawnEnemyEvent>,
+) {
+    let reloaded = script_events
+        .read()
+        .filter(|event| matches!(event, AssetEvent::Modified { .. }))
+        .count()
+        > 0;
+
+    let Some(script) = script.get() else {
+        return;
+    };
+
+    // Events are matched against the time window instead of an index so a reloaded script
+    // neither replays nor skips them
+    for event in script.events.iter() {
+        if !event.fires_between(director.previous_elapsed, state.elapsed) {
+            continue;
+        }
+
+        match &event.kind {
+            ScriptedEventKind::Horde {
+                archetype,
+                count,
+                strategy,
+            } => {
+                for _ in 0..*count {
+                    spawn_enemy_event_writter.send(SpawnEnemyEvent {
+                        strategy: strategy.clone(),
+                        ..SpawnEnemyEvent::new(archetype)
+                    });
+                }
+            }
+            ScriptedEventKind::Boss { archetype } => {
+                // Still behind its spawn warning
+                let warned = warning_query
+                    .iter()
+                    .any(|warning| warning.archetype() == archetype);
+
+                if boss_query.is_empty() && !warned {
+                    spawn_enemy_event_writter.send(SpawnEnemyEvent::new(archetype));
+                }
+            }
+        }
+    }
+
+    let wave = script.wave_at(state.elapsed);
+
+    // The archetype picked for the next spawn may not be part of the new wave
+    if reloaded || wave != director.wave {
+        director.next = None;
+    }
+
+    director.previous_elapsed = state.elapsed;
+    director.wave = wave;
+}
+
+/// Enemies behind a spawn warning count as alive already.
+type AliveFilter = Or<(With<Enemy>, With<SpawnWarning>)>;
+
+pub fn spawn_wave_enemies(
+    time: Res<Time>,
+    script: CurrentWaveScript,
+    archetypes: CurrentEnemyArchetypes,
+    scale: Res<DifficultyScale>,
+    mut director: ResMut<WaveDirector>,
+    enemy_query: Query<(), AliveFilter>,
+    mut spawn_enemy_event_writter: EventWriter<SpawnEnemyEvent>,
+) {
+    let Some(script) = script.get() else {
+        return;
+    };
+
+    let Some(wave) = director.wave.map(|index| &script.waves[index]) else {
+        return;
+    };
+
+    let max_alive = scale.max_alive(wave.max_alive);
+    let mut alive = enemy_query.iter().count();
+    // Formations spawn several enemies per request, each one paid for and counted
+    let enemies = wave.strategy.as_ref().map_or(1, SpawnStrategy::enemies);
+
+    // The budget does not pile up while the cap is reached
+    if alive + enemies <= max_alive {
+        director.budget += scale.budget(wave.budget) * time.delta_secs();
+    }
+
+    while alive + enemies <= max_alive {
+        let next = match director.next.take() {
+            Some(next) => next,
+            None => {
+                let Some(archetype) = wave.pick_archetype(&mut rand::thread_rng()) else {
+                    break;
+                };
+
+                archetype.to_string()
+            }
+        };
+
+        let Some((archetype, _)) = archetypes.get(&next) else {
+            if archetypes.loaded() {
+                // Dropped, keeping it would stop the wave for the rest of the run
+                warn!("Unknown enemy archetype {} in the wave script", next);
+            } else {
+                director.next = Some(next);
+            }
+
+            break;
+        };
+
+        if !director.try_spend(archetype.cost * enemies as f32) {
+            director.next = Some(next);
+            break;
+        }
+
+        alive += enemies;
+
+        spawn_enemy_event_writter.send(SpawnEnemyEvent {
+            strategy: wave.strategy.clone(),
+            ..SpawnEnemyEvent::new(&next)
+        });
+    }
+}
+
+type StragglerFilter = (With<Enemy>, Without<Boss>, Without<Player>);
+
+/// Brings enemies that fell too far behind back around the player, so they keep counting
+/// toward the max alive as a threat instead of being replaced by new spawns.
+pub fn recycle_stragglers(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<&mut Transform, StragglerFilter>,
+    settings: Res<SpawnSettings>,
+) {
+    let (Ok(window), Ok(player_transform)) = (window_query.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+
+    let view = Rect::new(0., 0., window.width(), window.height());
+    let player = player_transform.translation.truncate();
+    let recycle_distance = view.size().length() * RECYCLE_DISTANCE;
+
+    for mut transform in enemy_query.iter_mut() {
+        if transform.translation.truncate().distance(player) < recycle_distance {
+            continue;
+        }
+
+        let Some(positions) = settings.find_positions(
+            &SpawnStrategy::default(),
+            view,
+            player,
+            &mut rand::thread_rng(),
+        ) else {
+            continue;
+        };
+
+        transform.translation = positions[0].extend(transform.translation.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemy::archetype::EnemyArchetypes;
+
+    fn wave(start: f32, end: f32) -> Wave {
+        Wave {
+            start,
+            end,
+            budget: 1.,
+            max_alive: 10,
+            archetypes: vec![("chaser".to_string(), 1.)],
+            strategy: None,
+        }
+    }
+
+    #[test]
+    fn last_started_wave_keeps_running() {
+        let script = WaveScript {
+            waves: vec![wave(0., 10.), wave(10., 20.)],
+            events: vec![],
+            difficulty: Difficulty::default(),
+        };
+
+        assert_eq!(script.wave_at(5.), Some(0));
+        assert_eq!(script.wave_at(10.), Some(1));
+        assert_eq!(script.wave_at(100.), Some(1));
+    }
+
+    #[test]
+    fn repeating_event_fires_every_period() {
+        let event = ScriptedEvent {
+            at: 300.,
+            every: Some(300.),
+            kind: ScriptedEventKind::Boss {
+                archetype: "warden".to_string(),
+            },
+        };
+
+        assert!(!event.fires_between(0., 299.));
+        assert!(event.fires_between(299., 301.));
+        assert!(!event.fires_between(301., 599.));
+        assert!(event.fires_between(1799.5, 1800.5));
+
+        let once = ScriptedEvent {
+            every: None,
+            ..event
+        };
+
+        assert!(once.fires_between(299., 301.));
+        assert!(!once.fires_between(599., 601.));
+    }
+
+    #[test]
+    fn director_waits_until_it_affords_the_cost() {
+        let mut director = WaveDirector {
+            budget: 1.5,
+            ..default()
+        };
+
+        assert!(director.try_spend(1.));
+        assert!(!director.try_spend(1.));
+
+        director.budget += 0.5;
+
+        assert!(director.try_spend(1.));
+        assert_eq!(director.budget, 0.);
+    }
+
+    #[test]
+    fn bundled_wave_script_parses() {
+        let script: WaveScript =
+            bevy::asset::ron::de::from_str(include_str!("../../assets/default.waves.ron")).unwrap();
+
+        let archetypes: EnemyArchetypes =
+            bevy::asset::ron::de::from_str(include_str!("../../assets/enemies.archetypes.ron"))
+                .unwrap();
+        let exists = |id: &str| {
+            archetypes
+                .archetypes
+                .iter()
+                .any(|archetype| archetype.id == id)
+        };
+
+        assert_eq!(script.wave_at(0.), Some(0));
+
+        for wave in script.waves.iter() {
+            assert!(wave.pick_archetype(&mut rand::thread_rng()).is_some());
+            assert!(wave.archetypes.iter().all(|(id, _)| exists(id)));
+        }
+    }
+}